@@ -1,3 +1,268 @@
+use crate::FieldPath;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `Defaultable`",
+    label = "defaults are applied to fields through their `Defaultable` implementation",
+    note = "derive `Default` from `default_derive` for `{Self}`, or exclude the field with `#[cdefault(skip)]`"
+)]
 pub trait Defaultable {
-    fn apply_defaults(&mut self);
-}
\ No newline at end of file
+    /// Apply the defaults, taking the values referenced with `#[cdefault(from = "...")]` from `context`.
+    fn apply_defaults_with(&mut self, context: &DefaultContext);
+
+    /// Apply the defaults without context, defaults taken from the context are not applied.
+    fn apply_defaults(&mut self) {
+        self.apply_defaults_with(&DefaultContext::default());
+    }
+
+    /// Apply the defaults like [`Defaultable::apply_defaults_with`] and report the fields they changed.
+    ///
+    /// The fields are found by comparing the serde JSON form of the object, an error serializing it
+    /// is returned instead of a report. The defaults are not applied when the object itself fails to serialize.
+    fn apply_defaults_report_with(
+        &mut self,
+        context: &DefaultContext,
+    ) -> Result<DefaultingReport, serde_json::Error>
+    where
+        Self: Serialize,
+    {
+        let old = serde_json::to_value(&*self)?;
+        self.apply_defaults_with(context);
+        let new = serde_json::to_value(&*self)?;
+        Ok(DefaultingReport::diff(&old, &new))
+    }
+
+    /// Apply the defaults without context and report the fields they changed.
+    fn apply_defaults_report(&mut self) -> Result<DefaultingReport, serde_json::Error>
+    where
+        Self: Serialize,
+    {
+        self.apply_defaults_report_with(&DefaultContext::default())
+    }
+}
+
+/// DefaultedField is a field changed by applying defaults, with its values in their serde JSON form.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DefaultedField {
+    /// Location of the field, following the serde names, e.g. `spec.serviceName`
+    pub path: FieldPath,
+    /// Value before the defaults were applied, `null` when the field was not set
+    pub old: Value,
+    /// Value after the defaults were applied
+    pub new: Value,
+}
+
+/// DefaultingReport lists the fields changed by applying defaults, e.g. to record them as
+/// managed by a defaulter or to show server filled values.
+///
+/// The fields are found by comparing the serde JSON form of the object before and after the
+/// defaults were applied. Defaulting is idempotent when applying the defaults again reports no fields.
+/// Fields are listed in the order of their serde names, as serde JSON sorts object members.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DefaultingReport {
+    pub fields: Vec<DefaultedField>,
+}
+
+impl DefaultingReport {
+    /// Returns true when no field was changed
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// The paths of the changed fields
+    pub fn paths(&self) -> impl Iterator<Item = &FieldPath> {
+        self.fields.iter().map(|field| &field.path)
+    }
+
+    /// Compare two versions of an object. Objects and lists of the same length are compared
+    /// member by member, so a default within a list item is reported at the item field.
+    fn diff(old: &Value, new: &Value) -> Self {
+        let mut report = Self::default();
+        report.diff_at(&FieldPath::root(), old, new);
+        report
+    }
+
+    fn diff_at(&mut self, path: &FieldPath, old: &Value, new: &Value) {
+        match (old, new) {
+            (Value::Object(old_fields), Value::Object(new_fields)) => {
+                for (name, new_value) in new_fields {
+                    let old_value = old_fields.get(name).unwrap_or(&Value::Null);
+                    self.diff_at(&path.field(name), old_value, new_value);
+                }
+            }
+            (Value::Array(old_items), Value::Array(new_items)) if old_items.len() == new_items.len() => {
+                for (index, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                    self.diff_at(&path.index(index), old_item, new_item);
+                }
+            }
+            _ if old != new => self.fields.push(DefaultedField {
+                path: path.clone(),
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for DefaultingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            if field.old.is_null() {
+                write!(f, "Field '{}' defaulted to {}", field.path, field.new)?;
+            } else {
+                write!(f, "Field '{}' defaulted from {} to {}", field.path, field.old, field.new)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// DefaultContext carries the values defaults can be taken from with `#[cdefault(from = "...")]`,
+/// it is passed down unchanged to nested values. It holds:
+/// - `apiVersion`: the api version of the enclosing resource, e.g. `example.com/v1`
+/// - `metadata`: the `ObjectMeta` of the enclosing resource, e.g. `metadata.name` or `metadata.labels.tier`
+/// - `values`: values provided by the caller, e.g. `values.region`
+///
+/// Values are kept in their serde JSON form, so paths follow the serde names, e.g. `metadata.generateName`,
+/// and are converted to the type of the field they default.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DefaultContext {
+    root: Map<String, Value>,
+}
+
+impl DefaultContext {
+    /// The top level names of the context paths
+    pub const ROOTS: [&'static str; 3] = ["apiVersion", "metadata", "values"];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the api version of the enclosing resource
+    pub fn with_api_version(mut self, api_version: impl Into<String>) -> Self {
+        self.root
+            .insert("apiVersion".to_string(), Value::String(api_version.into()));
+        self
+    }
+
+    /// Set the metadata of the enclosing resource, e.g. its `ObjectMeta`
+    ///
+    /// # Panics
+    ///
+    /// Panics when `metadata` can not be serialized to JSON, e.g. a map with non-string keys.
+    pub fn with_metadata(mut self, metadata: &impl Serialize) -> Self {
+        let metadata = serde_json::to_value(metadata).unwrap_or_else(|err| {
+            panic!("the metadata of a DefaultContext must serialize to JSON: {err}")
+        });
+        self.root.insert("metadata".to_string(), metadata);
+        self
+    }
+
+    /// Add a value provided by the caller, referenced as `values.<name>`
+    ///
+    /// # Panics
+    ///
+    /// Panics when `value` can not be serialized to JSON, e.g. a map with non-string keys.
+    pub fn with_value(mut self, name: impl Into<String>, value: &impl Serialize) -> Self {
+        let name = name.into();
+        let value = serde_json::to_value(value).unwrap_or_else(|err| {
+            panic!("the value `{name}` of a DefaultContext must serialize to JSON: {err}")
+        });
+        let values = self
+            .root
+            .entry("values")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(values) = values {
+            values.insert(name, value);
+        }
+        self
+    }
+
+    /// Returns the value at a `.` separated path, e.g. `metadata.name`, or `None` when it is not set.
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        let mut segments = path.split('.');
+        let mut value = self.root.get(segments.next()?)?;
+        for segment in segments {
+            value = value.as_object()?.get(segment)?;
+        }
+        Some(value).filter(|value| !value.is_null())
+    }
+
+    /// Returns the value at `path` converted to `T`, or `None` when it is not set or has another type.
+    pub fn resolve<T: DeserializeOwned>(&self, path: &str) -> Option<T> {
+        self.get(path).and_then(|value| T::deserialize(value).ok())
+    }
+}
+
+/// Defaults are applied to the value when it is set.
+impl<T: Defaultable> Defaultable for Option<T> {
+    fn apply_defaults_with(&mut self, context: &DefaultContext) {
+        if let Some(value) = self {
+            value.apply_defaults_with(context);
+        }
+    }
+}
+
+impl<T: Defaultable> Defaultable for Vec<T> {
+    fn apply_defaults_with(&mut self, context: &DefaultContext) {
+        for item in self {
+            item.apply_defaults_with(context);
+        }
+    }
+}
+
+impl<K, V: Defaultable, S> Defaultable for HashMap<K, V, S> {
+    fn apply_defaults_with(&mut self, context: &DefaultContext) {
+        for value in self.values_mut() {
+            value.apply_defaults_with(context);
+        }
+    }
+}
+
+impl<K, V: Defaultable> Defaultable for BTreeMap<K, V> {
+    fn apply_defaults_with(&mut self, context: &DefaultContext) {
+        for value in self.values_mut() {
+            value.apply_defaults_with(context);
+        }
+    }
+}
+
+impl<T: Defaultable + ?Sized> Defaultable for Box<T> {
+    fn apply_defaults_with(&mut self, context: &DefaultContext) {
+        (**self).apply_defaults_with(context);
+    }
+}
+
+/// Primitives have no fields to default, their defaults are declared on the field holding them.
+macro_rules! impl_defaultable_for_primitives {
+    ($($typ:ty),* $(,)?) => {
+        $(
+            impl Defaultable for $typ {
+                fn apply_defaults_with(&mut self, _context: &DefaultContext) {}
+            }
+        )*
+    };
+}
+
+impl_defaultable_for_primitives!(
+    bool, char, String, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64,
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, NonZeroU8,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize, std::time::Duration,
+);
+
+/// A point in time is a single value like the primitives.
+impl<Tz: chrono::TimeZone> Defaultable for chrono::DateTime<Tz> {
+    fn apply_defaults_with(&mut self, _context: &DefaultContext) {}
+}
@@ -0,0 +1,1212 @@
+//! A small CEL-like expression language for validation rules, modelled after the
+//! `x-kubernetes-validations` rules of Kubernetes CRDs, e.g. `self.minReplicas <= self.maxReplicas`.
+//!
+//! Rules are evaluated against the JSON representation of a value, `self` refers to the value
+//! the rule is declared on, so field names follow the serde names, e.g. `self.minReplicas`.
+//! Transition rules refer to the previous version of the value as `oldSelf`, e.g. `self >= oldSelf`,
+//! and are only evaluated on updates.
+//!
+//! Supported are:
+//! - literals: `1`, `1.5`, `'text'`, `"text"`, `true`, `false`, `null` and lists `[1, 2]`
+//! - field selection `self.spec.name`, indexing `self.ports[0]` and `self.labels['tier']`
+//! - the operators `!`, `-`, `*`, `/`, `%`, `+`, `<`, `<=`, `>`, `>=`, `==`, `!=`, `in`, `&&`, `||` and `a ? b : c`
+//! - the functions `size(x)`, `has(x.field)`, `int(x)`, `double(x)` and `string(x)`
+//! - the methods `x.size()`, `x.contains(y)`, `x.startsWith(y)` and `x.endsWith(y)`
+//! - the macros `x.all(v, p)`, `x.exists(v, p)`, `x.exists_one(v, p)`, `x.map(v, e)` and `x.filter(v, p)`,
+//!   which iterate the items of a list or the keys of a map
+//!
+//! Expressions can be nested at most 100 levels deep, deeper expressions fail to compile.
+use crate::{FieldPath, Severity, ValidationError, ValidationErrorKind, ValidationErrors};
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+use std::borrow::Cow;
+use std::fmt;
+
+/// ExprError is returned when an expression can not be compiled or evaluated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExprError {
+    /// Description of the problem
+    pub message: String,
+    /// Byte offset in the expression source, for compile errors
+    pub position: Option<usize>,
+}
+
+impl ExprError {
+    fn compile(message: impl Into<String>, position: usize) -> Self {
+        Self {
+            message: message.into(),
+            position: Some(position),
+        }
+    }
+
+    fn eval(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            position: None,
+        }
+    }
+}
+
+impl fmt::Display for ExprError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{} at position {}", self.message, position),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for ExprError {}
+
+/// Program is a compiled expression.
+#[derive(Clone, Debug)]
+pub struct Program {
+    source: String,
+    root: Node,
+    is_transition: bool,
+}
+
+impl Program {
+    /// Compile `source`, the variables in scope are `self` and, for transition rules, `oldSelf`
+    pub fn compile(source: &str) -> Result<Self, ExprError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            current: 0,
+            depth: 0,
+            variables: vec!["self".to_string(), "oldSelf".to_string()],
+            uses_old_self: false,
+        };
+        let (root, _) = parser.parse_expression()?;
+        if let Some((token, position)) = parser.tokens.get(parser.current) {
+            return Err(ExprError::compile(format!("unexpected {token}"), *position));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            root,
+            is_transition: parser.uses_old_self,
+        })
+    }
+
+    /// The source the program was compiled from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the program refers to `oldSelf`, such a transition rule is only evaluated on updates
+    pub fn is_transition(&self) -> bool {
+        self.is_transition
+    }
+
+    /// Evaluate the program with `self` bound to `value`
+    pub fn evaluate(&self, value: &Value) -> Result<Value, ExprError> {
+        let mut scope = vec![("self", Cow::Borrowed(value))];
+        evaluate(&self.root, &mut scope).map(Cow::into_owned)
+    }
+
+    /// Evaluate the program with `self` bound to `value` and `oldSelf` bound to `old`
+    pub fn evaluate_transition(&self, value: &Value, old: &Value) -> Result<Value, ExprError> {
+        let mut scope = vec![
+            ("self", Cow::Borrowed(value)),
+            ("oldSelf", Cow::Borrowed(old)),
+        ];
+        evaluate(&self.root, &mut scope).map(Cow::into_owned)
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// ExpressionRule is a rule that must evaluate to `true` for a value to be valid,
+/// with an optional message template describing the violation.
+#[derive(Clone, Debug)]
+pub struct ExpressionRule {
+    program: Program,
+    message: Option<String>,
+    code: Option<String>,
+    severity: Severity,
+}
+
+impl ExpressionRule {
+    /// Compile the rule `rule`, e.g. `self.minReplicas <= self.maxReplicas`
+    pub fn new(rule: &str) -> Result<Self, ExprError> {
+        Ok(Self {
+            program: Program::compile(rule)?,
+            message: None,
+            code: None,
+            severity: Severity::Error,
+        })
+    }
+
+    /// Set the message reported when the rule is violated. Like the `message` of a field rule it is a
+    /// template replacing the default text, e.g. `{path} must not exceed maxReplicas`, see
+    /// [`TEMPLATE_PLACEHOLDERS`](crate::validation_error::TEMPLATE_PLACEHOLDERS).
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Set the code reported instead of the `rule` kind
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Set the severity of violations, e.g. to only warn
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// The source of the rule
+    pub fn rule(&self) -> &str {
+        self.program.source()
+    }
+
+    /// Whether the rule refers to `oldSelf` and is only evaluated on updates
+    pub fn is_transition(&self) -> bool {
+        self.program.is_transition()
+    }
+
+    /// Evaluate the rule against the JSON representation of `value` located at `path`,
+    /// recording a violation or evaluation failure in `errors`. Transition rules are skipped.
+    pub fn validate_at<T: Serialize + ?Sized>(
+        &self,
+        value: &T,
+        path: &FieldPath,
+        errors: &mut ValidationErrors,
+    ) {
+        if self.is_transition() {
+            return;
+        }
+        let result = serde_json::to_value(value)
+            .map_err(|err| format!("failed to serialize value: {err}"))
+            .and_then(|value| {
+                self.program
+                    .evaluate(&value)
+                    .map_err(|err| format!("failed to evaluate rule: {err}"))
+            });
+        self.report(result, path, errors);
+    }
+
+    /// Evaluate a transition rule against the JSON representations of `value` and its previous
+    /// version `old`, located at `path`. Rules that do not refer to `oldSelf` are skipped.
+    pub fn validate_transition_at<T: Serialize + ?Sized>(
+        &self,
+        value: &T,
+        old: &T,
+        path: &FieldPath,
+        errors: &mut ValidationErrors,
+    ) {
+        if !self.is_transition() {
+            return;
+        }
+        let result = serde_json::to_value(value)
+            .and_then(|value| Ok((value, serde_json::to_value(old)?)))
+            .map_err(|err| format!("failed to serialize value: {err}"))
+            .and_then(|(value, old)| {
+                self.program
+                    .evaluate_transition(&value, &old)
+                    .map_err(|err| format!("failed to evaluate rule: {err}"))
+            });
+        self.report(result, path, errors);
+    }
+
+    fn report(
+        &self,
+        result: Result<Value, String>,
+        path: &FieldPath,
+        errors: &mut ValidationErrors,
+    ) {
+        let mut error = ValidationError::new(path.clone(), ValidationErrorKind::Rule)
+            .with_constraint(self.rule())
+            .with_severity(self.severity);
+        error.code = self.code.clone();
+        match result {
+            Ok(Value::Bool(true)) => {}
+            Ok(Value::Bool(false)) => match &self.message {
+                Some(message) => errors.push(error.with_template(message.clone())),
+                None => errors.push(error),
+            },
+            Ok(other) => errors.push(error.with_message(format!(
+                "rule must evaluate to a bool, got {}",
+                type_name(&other)
+            ))),
+            Err(message) => errors.push(error.with_message(message)),
+        }
+    }
+}
+
+// ---- syntax ----
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Int(i64),
+    Double(f64),
+    Str(String),
+    Ident(String),
+    Punct(&'static str),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Int(value) => write!(f, "'{value}'"),
+            Token::Double(value) => write!(f, "'{value}'"),
+            Token::Str(value) => write!(f, "{value:?}"),
+            Token::Ident(name) => write!(f, "'{name}'"),
+            Token::Punct(punct) => write!(f, "'{punct}'"),
+        }
+    }
+}
+
+/// Punctuation, longer tokens first so that `<=` is not read as `<` and `=`
+const PUNCTS: [&str; 22] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "+", "-", "*", "/", "%", "?", ":", ".", ",",
+    "(", ")", "[", "]",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(Token, usize)>, ExprError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() {
+            let mut end = position;
+            let mut is_double = false;
+            while let Some(&(i, c)) = chars.peek() {
+                let is_fraction = c == '.'
+                    && !is_double
+                    && source[i + 1..].starts_with(|c: char| c.is_ascii_digit());
+                if !(c.is_ascii_digit() || is_fraction) {
+                    break;
+                }
+                is_double |= is_fraction;
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            let literal = &source[position..end];
+            let token = if is_double {
+                literal.parse().map(Token::Double).ok()
+            } else {
+                literal.parse().map(Token::Int).ok()
+            };
+            let token = token.ok_or_else(|| {
+                ExprError::compile(format!("invalid number literal '{literal}'"), position)
+            })?;
+            tokens.push((token, position));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = position;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push((Token::Ident(source[position..end].to_string()), position));
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '\\')) => match chars.next() {
+                        Some((_, 'n')) => value.push('\n'),
+                        Some((_, 't')) => value.push('\t'),
+                        Some((_, 'r')) => value.push('\r'),
+                        Some((_, escaped @ ('\\' | '\'' | '"'))) => value.push(escaped),
+                        Some((i, escaped)) => {
+                            return Err(ExprError::compile(
+                                format!("invalid escape sequence '\\{escaped}'"),
+                                i,
+                            ))
+                        }
+                        None => break,
+                    },
+                    Some((_, quote)) if quote == c => {
+                        tokens.push((Token::Str(value), position));
+                        break;
+                    }
+                    Some((_, other)) => value.push(other),
+                    None => {
+                        return Err(ExprError::compile("unterminated string literal", position))
+                    }
+                }
+            }
+        } else {
+            let Some(punct) = PUNCTS
+                .iter()
+                .find(|punct| source[position..].starts_with(**punct))
+            else {
+                return Err(ExprError::compile(
+                    format!("unexpected character '{c}'"),
+                    position,
+                ));
+            };
+            for _ in 0..punct.len() {
+                chars.next();
+            }
+            tokens.push((Token::Punct(punct), position));
+        }
+    }
+    Ok(tokens)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum UnaryOp {
+    Not,
+    Negate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Or,
+    And,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    In,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Function {
+    Size,
+    Int,
+    Double,
+    String,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Macro {
+    All,
+    Exists,
+    ExistsOne,
+    Map,
+    Filter,
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Literal(Value),
+    List(Vec<Node>),
+    Variable(String),
+    Select(Box<Node>, String),
+    Has(Box<Node>, String),
+    Index(Box<Node>, Box<Node>),
+    /// A function call, the target of a method call is the first argument
+    Call(Function, Vec<Node>),
+    Unary(UnaryOp, Box<Node>),
+    Binary(BinaryOp, Box<Node>, Box<Node>),
+    Conditional(Box<Node>, Box<Node>, Box<Node>),
+    Comprehension {
+        kind: Macro,
+        target: Box<Node>,
+        variable: String,
+        body: Box<Node>,
+    },
+}
+
+/// The maximum nesting depth of an expression, like the nesting limit of CEL. Parsing and evaluation
+/// recurse along the nesting, so the limit keeps input like `((((...))))` from overflowing the stack.
+const MAX_DEPTH: usize = 100;
+
+/// A parsed node with its height, the number of nodes on its longest path to a leaf
+type Parsed = (Node, usize);
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    current: usize,
+    /// Nesting of the part being parsed, at most [`MAX_DEPTH`]
+    depth: usize,
+    /// Variables in scope, `self`, `oldSelf` and the variables of the enclosing macros
+    variables: Vec<String>,
+    /// Whether the expression refers to `oldSelf`
+    uses_old_self: bool,
+}
+
+impl Parser {
+    fn peek_punct(&self, punct: &str) -> bool {
+        matches!(self.tokens.get(self.current), Some((Token::Punct(p), _)) if *p == punct)
+    }
+
+    fn peek_ident(&self, name: &str) -> bool {
+        matches!(self.tokens.get(self.current), Some((Token::Ident(ident), _)) if ident == name)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.current)
+            .or(self.tokens.last())
+            .map_or(0, |(_, position)| *position)
+    }
+
+    fn next(&mut self) -> Result<(Token, usize), ExprError> {
+        let token =
+            self.tokens.get(self.current).cloned().ok_or_else(|| {
+                ExprError::compile("unexpected end of expression", self.position())
+            })?;
+        self.current += 1;
+        Ok(token)
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        if self.peek_punct(punct) {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<(), ExprError> {
+        match self.next()? {
+            (Token::Punct(p), _) if p == punct => Ok(()),
+            (token, position) => Err(ExprError::compile(
+                format!("expected '{punct}', found {token}"),
+                position,
+            )),
+        }
+    }
+
+    fn expect_ident(&mut self) -> Result<(String, usize), ExprError> {
+        match self.next()? {
+            (Token::Ident(name), position) => Ok((name, position)),
+            (token, position) => Err(ExprError::compile(
+                format!("expected an identifier, found {token}"),
+                position,
+            )),
+        }
+    }
+
+    /// Parse a nested part of the expression, failing when the nesting exceeds [`MAX_DEPTH`]
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ExprError>,
+    ) -> Result<T, ExprError> {
+        if self.depth == MAX_DEPTH {
+            return Err(too_deep(self.position()));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn parse_expression(&mut self) -> Result<Parsed, ExprError> {
+        self.nested(Self::parse_conditional)
+    }
+
+    fn parse_conditional(&mut self) -> Result<Parsed, ExprError> {
+        let condition = self.parse_or()?;
+        let position = self.position();
+        if !self.eat_punct("?") {
+            return Ok(condition);
+        }
+        let then = self.parse_expression()?;
+        self.expect_punct(":")?;
+        let otherwise = self.parse_expression()?;
+        let heights = [condition.1, then.1, otherwise.1];
+        nest(
+            Node::Conditional(
+                Box::new(condition.0),
+                Box::new(then.0),
+                Box::new(otherwise.0),
+            ),
+            &heights,
+            position,
+        )
+    }
+
+    fn parse_or(&mut self) -> Result<Parsed, ExprError> {
+        let mut left = self.parse_and()?;
+        loop {
+            let position = self.position();
+            if !self.eat_punct("||") {
+                return Ok(left);
+            }
+            let right = self.parse_and()?;
+            left = binary_node(BinaryOp::Or, left, right, position)?;
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Parsed, ExprError> {
+        let mut left = self.parse_relation()?;
+        loop {
+            let position = self.position();
+            if !self.eat_punct("&&") {
+                return Ok(left);
+            }
+            let right = self.parse_relation()?;
+            left = binary_node(BinaryOp::And, left, right, position)?;
+        }
+    }
+
+    fn parse_relation(&mut self) -> Result<Parsed, ExprError> {
+        let mut left = self.parse_addition()?;
+        loop {
+            let position = self.position();
+            let op = if self.eat_punct("<") {
+                BinaryOp::Less
+            } else if self.eat_punct("<=") {
+                BinaryOp::LessEqual
+            } else if self.eat_punct(">") {
+                BinaryOp::Greater
+            } else if self.eat_punct(">=") {
+                BinaryOp::GreaterEqual
+            } else if self.eat_punct("==") {
+                BinaryOp::Equal
+            } else if self.eat_punct("!=") {
+                BinaryOp::NotEqual
+            } else if self.peek_ident("in") {
+                self.current += 1;
+                BinaryOp::In
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_addition()?;
+            left = binary_node(op, left, right, position)?;
+        }
+    }
+
+    fn parse_addition(&mut self) -> Result<Parsed, ExprError> {
+        let mut left = self.parse_multiplication()?;
+        loop {
+            let position = self.position();
+            let op = if self.eat_punct("+") {
+                BinaryOp::Add
+            } else if self.eat_punct("-") {
+                BinaryOp::Subtract
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_multiplication()?;
+            left = binary_node(op, left, right, position)?;
+        }
+    }
+
+    fn parse_multiplication(&mut self) -> Result<Parsed, ExprError> {
+        let mut left = self.parse_unary()?;
+        loop {
+            let position = self.position();
+            let op = if self.eat_punct("*") {
+                BinaryOp::Multiply
+            } else if self.eat_punct("/") {
+                BinaryOp::Divide
+            } else if self.eat_punct("%") {
+                BinaryOp::Remainder
+            } else {
+                return Ok(left);
+            };
+            let right = self.parse_unary()?;
+            left = binary_node(op, left, right, position)?;
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Parsed, ExprError> {
+        let position = self.position();
+        let op = if self.eat_punct("!") {
+            UnaryOp::Not
+        } else if self.eat_punct("-") {
+            UnaryOp::Negate
+        } else {
+            return self.parse_member();
+        };
+        let (operand, height) = self.nested(Self::parse_unary)?;
+        nest(Node::Unary(op, Box::new(operand)), &[height], position)
+    }
+
+    fn parse_member(&mut self) -> Result<Parsed, ExprError> {
+        let mut node = self.parse_primary()?;
+        loop {
+            let position = self.position();
+            if self.eat_punct(".") {
+                let (name, position) = self.expect_ident()?;
+                if !self.eat_punct("(") {
+                    node = nest(Node::Select(Box::new(node.0), name), &[node.1], position)?;
+                    continue;
+                }
+                if let Some(kind) = macro_for_name(&name) {
+                    let (variable, _) = self.expect_ident()?;
+                    self.expect_punct(",")?;
+                    self.variables.push(variable.clone());
+                    let body = self.parse_expression();
+                    self.variables.pop();
+                    let body = body?;
+                    self.expect_punct(")")?;
+                    let heights = [node.1, body.1];
+                    node = nest(
+                        Node::Comprehension {
+                            kind,
+                            target: Box::new(node.0),
+                            variable,
+                            body: Box::new(body.0),
+                        },
+                        &heights,
+                        position,
+                    )?;
+                    continue;
+                }
+                let function = method_for_name(&name).ok_or_else(|| {
+                    ExprError::compile(format!("unknown method '{name}'"), position)
+                })?;
+                let (args, heights): (Vec<_>, Vec<_>) =
+                    std::iter::once(node).chain(self.parse_arguments()?).unzip();
+                check_arity(function, &name, args.len(), position)?;
+                node = nest(Node::Call(function, args), &heights, position)?;
+            } else if self.eat_punct("[") {
+                let index = self.parse_expression()?;
+                self.expect_punct("]")?;
+                let heights = [node.1, index.1];
+                node = nest(
+                    Node::Index(Box::new(node.0), Box::new(index.0)),
+                    &heights,
+                    position,
+                )?;
+            } else {
+                return Ok(node);
+            }
+        }
+    }
+
+    /// Parse the arguments of a call after the opening parenthesis
+    fn parse_arguments(&mut self) -> Result<Vec<Parsed>, ExprError> {
+        let mut args = Vec::new();
+        if self.eat_punct(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.parse_expression()?);
+            if self.eat_punct(")") {
+                return Ok(args);
+            }
+            self.expect_punct(",")?;
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Parsed, ExprError> {
+        match self.next()? {
+            (Token::Int(value), _) => Ok((Node::Literal(Value::from(value)), 1)),
+            (Token::Double(value), position) => Number::from_f64(value)
+                .map(|number| (Node::Literal(Value::Number(number)), 1))
+                .ok_or_else(|| ExprError::compile("invalid number literal", position)),
+            (Token::Str(value), _) => Ok((Node::Literal(Value::String(value)), 1)),
+            (Token::Punct("("), _) => {
+                let node = self.parse_expression()?;
+                self.expect_punct(")")?;
+                Ok(node)
+            }
+            (Token::Punct("["), position) => {
+                let mut items = Vec::new();
+                if !self.eat_punct("]") {
+                    loop {
+                        items.push(self.parse_expression()?);
+                        if self.eat_punct("]") {
+                            break;
+                        }
+                        self.expect_punct(",")?;
+                    }
+                }
+                let (items, heights): (Vec<_>, Vec<_>) = items.into_iter().unzip();
+                nest(Node::List(items), &heights, position)
+            }
+            (Token::Ident(name), position) => match name.as_str() {
+                "true" => Ok((Node::Literal(Value::Bool(true)), 1)),
+                "false" => Ok((Node::Literal(Value::Bool(false)), 1)),
+                "null" => Ok((Node::Literal(Value::Null), 1)),
+                "has" if self.eat_punct("(") => {
+                    let (argument, height) = self.parse_expression()?;
+                    self.expect_punct(")")?;
+                    match argument {
+                        Node::Select(target, field) => Ok((Node::Has(target, field), height)),
+                        _ => Err(ExprError::compile(
+                            "has() requires a field selection, e.g. has(self.field)",
+                            position,
+                        )),
+                    }
+                }
+                _ if self.eat_punct("(") => {
+                    let function = function_for_name(&name).ok_or_else(|| {
+                        ExprError::compile(format!("unknown function '{name}'"), position)
+                    })?;
+                    let (args, heights): (Vec<_>, Vec<_>) =
+                        self.parse_arguments()?.into_iter().unzip();
+                    check_arity(function, &name, args.len(), position)?;
+                    nest(Node::Call(function, args), &heights, position)
+                }
+                _ if self.variables.contains(&name) => {
+                    self.uses_old_self |= name == "oldSelf";
+                    Ok((Node::Variable(name), 1))
+                }
+                _ => Err(ExprError::compile(
+                    format!("undeclared reference to '{name}'"),
+                    position,
+                )),
+            },
+            (token, position) => Err(ExprError::compile(format!("unexpected {token}"), position)),
+        }
+    }
+}
+
+/// Build a node over children of the given heights, failing when it is nested deeper than [`MAX_DEPTH`]
+fn nest(node: Node, heights: &[usize], position: usize) -> Result<Parsed, ExprError> {
+    let height = heights.iter().max().map_or(1, |height| height + 1);
+    if height > MAX_DEPTH {
+        return Err(too_deep(position));
+    }
+    Ok((node, height))
+}
+
+fn binary_node(
+    op: BinaryOp,
+    left: Parsed,
+    right: Parsed,
+    position: usize,
+) -> Result<Parsed, ExprError> {
+    nest(
+        Node::Binary(op, Box::new(left.0), Box::new(right.0)),
+        &[left.1, right.1],
+        position,
+    )
+}
+
+fn too_deep(position: usize) -> ExprError {
+    ExprError::compile(
+        format!("expression exceeds the maximum nesting depth of {MAX_DEPTH}"),
+        position,
+    )
+}
+
+fn macro_for_name(name: &str) -> Option<Macro> {
+    match name {
+        "all" => Some(Macro::All),
+        "exists" => Some(Macro::Exists),
+        "exists_one" => Some(Macro::ExistsOne),
+        "map" => Some(Macro::Map),
+        "filter" => Some(Macro::Filter),
+        _ => None,
+    }
+}
+
+fn function_for_name(name: &str) -> Option<Function> {
+    match name {
+        "size" => Some(Function::Size),
+        "int" => Some(Function::Int),
+        "double" => Some(Function::Double),
+        "string" => Some(Function::String),
+        _ => None,
+    }
+}
+
+fn method_for_name(name: &str) -> Option<Function> {
+    match name {
+        "size" => Some(Function::Size),
+        "contains" => Some(Function::Contains),
+        "startsWith" => Some(Function::StartsWith),
+        "endsWith" => Some(Function::EndsWith),
+        _ => None,
+    }
+}
+
+/// Check the number of arguments, including the target of a method call
+fn check_arity(
+    function: Function,
+    name: &str,
+    count: usize,
+    position: usize,
+) -> Result<(), ExprError> {
+    let expected = match function {
+        Function::Size | Function::Int | Function::Double | Function::String => 1,
+        Function::Contains | Function::StartsWith | Function::EndsWith => 2,
+    };
+    if count != expected {
+        return Err(ExprError::compile(
+            format!("wrong number of arguments for '{name}'"),
+            position,
+        ));
+    }
+    Ok(())
+}
+
+// ---- evaluation ----
+
+/// Variables in scope with their values. Values taken from the evaluated objects are borrowed,
+/// so selecting a field or an item does not copy the object.
+type Scope<'a> = Vec<(&'a str, Cow<'a, Value>)>;
+
+fn evaluate<'a>(node: &'a Node, scope: &mut Scope<'a>) -> Result<Cow<'a, Value>, ExprError> {
+    match node {
+        Node::Literal(value) => Ok(Cow::Borrowed(value)),
+        Node::List(items) => items
+            .iter()
+            .map(|item| evaluate(item, scope).map(Cow::into_owned))
+            .collect::<Result<_, _>>()
+            .map(|items| Cow::Owned(Value::Array(items))),
+        Node::Variable(name) => scope
+            .iter()
+            .rev()
+            .find(|(variable, _)| variable == name)
+            .map(|(_, value)| value.clone())
+            .ok_or_else(|| ExprError::eval(format!("undeclared reference to '{name}'"))),
+        Node::Select(target, field) => {
+            let target = evaluate(target, scope)?;
+            if !target.is_object() {
+                return Err(ExprError::eval(format!(
+                    "cannot select field '{field}' from {}",
+                    type_name(&target)
+                )));
+            }
+            member(target, |value| value.get(field))
+                .ok_or_else(|| ExprError::eval(format!("no such key: {field}")))
+        }
+        Node::Has(target, field) => match &*evaluate(target, scope)? {
+            Value::Object(map) => Ok(Cow::Owned(Value::Bool(
+                map.get(field).is_some_and(|value| !value.is_null()),
+            ))),
+            other => Err(ExprError::eval(format!(
+                "cannot test field '{field}' of {}",
+                type_name(other)
+            ))),
+        },
+        Node::Index(target, index) => {
+            let target = evaluate(target, scope)?;
+            let index = evaluate(index, scope)?;
+            match (&*target, &*index) {
+                (Value::Array(_), Value::Number(number)) => {
+                    let item = number.as_u64().and_then(|i| usize::try_from(i).ok());
+                    member(target, |value| value.get(item?))
+                        .ok_or_else(|| ExprError::eval(format!("index out of bounds: {number}")))
+                }
+                (Value::Object(_), Value::String(key)) => member(target, |value| value.get(key))
+                    .ok_or_else(|| ExprError::eval(format!("no such key: {key}"))),
+                _ => Err(no_overload("[]", &[&target, &index])),
+            }
+        }
+        Node::Call(function, args) => {
+            let args = args
+                .iter()
+                .map(|arg| evaluate(arg, scope))
+                .collect::<Result<Vec<_>, _>>()?;
+            call(
+                *function,
+                &args.iter().map(|arg| &**arg).collect::<Vec<_>>(),
+            )
+            .map(Cow::Owned)
+        }
+        Node::Unary(op, operand) => {
+            let operand = evaluate(operand, scope)?;
+            match (op, &*operand) {
+                (UnaryOp::Not, Value::Bool(value)) => Ok(Cow::Owned(Value::Bool(!value))),
+                (UnaryOp::Negate, Value::Number(_)) => {
+                    arithmetic(BinaryOp::Subtract, &Value::from(0), &operand).map(Cow::Owned)
+                }
+                (UnaryOp::Not, _) => Err(no_overload("!", &[&operand])),
+                (UnaryOp::Negate, _) => Err(no_overload("-", &[&operand])),
+            }
+        }
+        Node::Binary(BinaryOp::And, left, right) => {
+            if !as_bool(&*evaluate(left, scope)?, "&&")? {
+                return Ok(Cow::Owned(Value::Bool(false)));
+            }
+            Ok(Cow::Owned(Value::Bool(as_bool(
+                &*evaluate(right, scope)?,
+                "&&",
+            )?)))
+        }
+        Node::Binary(BinaryOp::Or, left, right) => {
+            if as_bool(&*evaluate(left, scope)?, "||")? {
+                return Ok(Cow::Owned(Value::Bool(true)));
+            }
+            Ok(Cow::Owned(Value::Bool(as_bool(
+                &*evaluate(right, scope)?,
+                "||",
+            )?)))
+        }
+        Node::Binary(op, left, right) => {
+            let left = evaluate(left, scope)?;
+            let right = evaluate(right, scope)?;
+            binary(*op, &left, &right).map(Cow::Owned)
+        }
+        Node::Conditional(condition, then, otherwise) => {
+            if as_bool(&*evaluate(condition, scope)?, "?:")? {
+                evaluate(then, scope)
+            } else {
+                evaluate(otherwise, scope)
+            }
+        }
+        Node::Comprehension {
+            kind,
+            target,
+            variable,
+            body,
+        } => {
+            let items: Vec<Cow<'a, Value>> = match evaluate(target, scope)? {
+                Cow::Borrowed(Value::Array(items)) => items.iter().map(Cow::Borrowed).collect(),
+                Cow::Owned(Value::Array(items)) => items.into_iter().map(Cow::Owned).collect(),
+                Cow::Borrowed(Value::Object(map)) => map
+                    .keys()
+                    .map(|key| Cow::Owned(Value::String(key.clone())))
+                    .collect(),
+                Cow::Owned(Value::Object(map)) => map
+                    .into_iter()
+                    .map(|(key, _)| Cow::Owned(Value::String(key)))
+                    .collect(),
+                other => {
+                    return Err(ExprError::eval(format!(
+                        "cannot iterate over {}",
+                        type_name(&other)
+                    )))
+                }
+            };
+            let mut results = Vec::with_capacity(items.len());
+            for item in items {
+                scope.push((variable.as_str(), item));
+                let result = evaluate(body, scope);
+                let (_, item) = scope.pop().expect("the item was pushed above");
+                results.push((item, result?));
+            }
+            comprehension(*kind, results).map(Cow::Owned)
+        }
+    }
+}
+
+/// Returns the member of `target` found by `get`, borrowed when `target` is borrowed.
+fn member<'a>(
+    target: Cow<'a, Value>,
+    get: impl for<'v> Fn(&'v Value) -> Option<&'v Value>,
+) -> Option<Cow<'a, Value>> {
+    match target {
+        Cow::Borrowed(value) => get(value).map(Cow::Borrowed),
+        Cow::Owned(value) => get(&value).cloned().map(Cow::Owned),
+    }
+}
+
+fn comprehension(kind: Macro, results: Vec<(Cow<Value>, Cow<Value>)>) -> Result<Value, ExprError> {
+    let name = match kind {
+        Macro::All => "all",
+        Macro::Exists => "exists",
+        Macro::ExistsOne => "exists_one",
+        Macro::Map => "map",
+        Macro::Filter => "filter",
+    };
+    if kind == Macro::Map {
+        return Ok(Value::Array(
+            results
+                .into_iter()
+                .map(|(_, result)| result.into_owned())
+                .collect(),
+        ));
+    }
+    let count = results.len();
+    let mut matches = Vec::new();
+    for (item, result) in results {
+        if as_bool(&result, name)? {
+            matches.push(item.into_owned());
+        }
+    }
+    Ok(match kind {
+        Macro::All => Value::Bool(matches.len() == count),
+        Macro::Exists => Value::Bool(!matches.is_empty()),
+        Macro::ExistsOne => Value::Bool(matches.len() == 1),
+        _ => Value::Array(matches),
+    })
+}
+
+fn call(function: Function, args: &[&Value]) -> Result<Value, ExprError> {
+    match (function, args) {
+        (Function::Size, [Value::String(value)]) => Ok(Value::from(value.chars().count())),
+        (Function::Size, [Value::Array(items)]) => Ok(Value::from(items.len())),
+        (Function::Size, [Value::Object(map)]) => Ok(Value::from(map.len())),
+        (Function::Int, [Value::Number(number)]) => match number.as_i64() {
+            Some(value) => Ok(Value::from(value)),
+            None => Ok(Value::from(
+                number.as_f64().unwrap_or_default().trunc() as i64
+            )),
+        },
+        (Function::Int, [Value::String(value)]) => value
+            .parse::<i64>()
+            .map(Value::from)
+            .map_err(|_| ExprError::eval(format!("cannot convert '{value}' to int"))),
+        (Function::Double, [Value::Number(number)]) => double(number.as_f64().unwrap_or_default()),
+        (Function::Double, [Value::String(value)]) => value
+            .parse::<f64>()
+            .map_err(|_| ExprError::eval(format!("cannot convert '{value}' to double")))
+            .and_then(double),
+        (Function::String, [Value::String(value)]) => Ok(Value::String(value.to_string())),
+        (Function::String, [value @ (Value::Number(_) | Value::Bool(_))]) => {
+            Ok(Value::String(value.to_string()))
+        }
+        (Function::Contains, [Value::String(value), Value::String(part)]) => {
+            Ok(Value::Bool(value.contains(part.as_str())))
+        }
+        (Function::Contains, [Value::Array(items), item]) => {
+            Ok(Value::Bool(items.iter().any(|other| equals(other, item))))
+        }
+        (Function::StartsWith, [Value::String(value), Value::String(prefix)]) => {
+            Ok(Value::Bool(value.starts_with(prefix.as_str())))
+        }
+        (Function::EndsWith, [Value::String(value), Value::String(suffix)]) => {
+            Ok(Value::Bool(value.ends_with(suffix.as_str())))
+        }
+        _ => {
+            let name = match function {
+                Function::Size => "size",
+                Function::Int => "int",
+                Function::Double => "double",
+                Function::String => "string",
+                Function::Contains => "contains",
+                Function::StartsWith => "startsWith",
+                Function::EndsWith => "endsWith",
+            };
+            Err(no_overload(name, args))
+        }
+    }
+}
+
+fn binary(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, ExprError> {
+    match op {
+        BinaryOp::Equal => Ok(Value::Bool(equals(left, right))),
+        BinaryOp::NotEqual => Ok(Value::Bool(!equals(left, right))),
+        BinaryOp::In => match right {
+            Value::Array(items) => Ok(Value::Bool(items.iter().any(|item| equals(item, left)))),
+            Value::Object(map) => match left {
+                Value::String(key) => Ok(Value::Bool(map.contains_key(key))),
+                _ => Err(no_overload("in", &[left, right])),
+            },
+            _ => Err(no_overload("in", &[left, right])),
+        },
+        BinaryOp::Less | BinaryOp::LessEqual | BinaryOp::Greater | BinaryOp::GreaterEqual => {
+            let (symbol, accept): (&str, fn(std::cmp::Ordering) -> bool) = match op {
+                BinaryOp::Less => ("<", std::cmp::Ordering::is_lt),
+                BinaryOp::LessEqual => ("<=", std::cmp::Ordering::is_le),
+                BinaryOp::Greater => (">", std::cmp::Ordering::is_gt),
+                _ => (">=", std::cmp::Ordering::is_ge),
+            };
+            let ordering = match (left, right) {
+                (Value::Number(a), Value::Number(b)) => compare_numbers(a, b),
+                (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+                _ => None,
+            };
+            ordering
+                .map(|ordering| Value::Bool(accept(ordering)))
+                .ok_or_else(|| no_overload(symbol, &[left, right]))
+        }
+        BinaryOp::Add => match (left, right) {
+            (Value::String(a), Value::String(b)) => Ok(Value::String(format!("{a}{b}"))),
+            (Value::Array(a), Value::Array(b)) => {
+                Ok(Value::Array(a.iter().chain(b).cloned().collect()))
+            }
+            _ => arithmetic(op, left, right),
+        },
+        _ => arithmetic(op, left, right),
+    }
+}
+
+fn arithmetic(op: BinaryOp, left: &Value, right: &Value) -> Result<Value, ExprError> {
+    let symbol = match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+        _ => "%",
+    };
+    let (Value::Number(a), Value::Number(b)) = (left, right) else {
+        return Err(no_overload(symbol, &[left, right]));
+    };
+    if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+        let result = match op {
+            BinaryOp::Add => a.checked_add(b),
+            BinaryOp::Subtract => a.checked_sub(b),
+            BinaryOp::Multiply => a.checked_mul(b),
+            BinaryOp::Divide if b == 0 => return Err(ExprError::eval("division by zero")),
+            BinaryOp::Divide => a.checked_div(b),
+            _ if b == 0 => return Err(ExprError::eval("modulus by zero")),
+            _ => a.checked_rem(b),
+        };
+        return result
+            .map(Value::from)
+            .ok_or_else(|| ExprError::eval("integer overflow"));
+    }
+    let (a, b) = (
+        a.as_f64().unwrap_or_default(),
+        b.as_f64().unwrap_or_default(),
+    );
+    double(match op {
+        BinaryOp::Add => a + b,
+        BinaryOp::Subtract => a - b,
+        BinaryOp::Multiply => a * b,
+        BinaryOp::Divide => a / b,
+        _ => a % b,
+    })
+}
+
+fn double(value: f64) -> Result<Value, ExprError> {
+    Number::from_f64(value)
+        .map(Value::Number)
+        .ok_or_else(|| ExprError::eval("result is not a finite number"))
+}
+
+fn compare_numbers(a: &Number, b: &Number) -> Option<std::cmp::Ordering> {
+    match (a.as_i64(), b.as_i64()) {
+        (Some(a), Some(b)) => Some(a.cmp(&b)),
+        _ => a.as_f64()?.partial_cmp(&b.as_f64()?),
+    }
+}
+
+/// Equality that compares numbers by value, so that `1 == 1.0`
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => compare_numbers(a, b).is_some_and(|o| o.is_eq()),
+        (Value::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equals(a, b))
+        }
+        (Value::Object(a), Value::Object(b)) => objects_equal(a, b),
+        _ => left == right,
+    }
+}
+
+fn objects_equal(a: &Map<String, Value>, b: &Map<String, Value>) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(key, value)| b.get(key).is_some_and(|other| equals(value, other)))
+}
+
+fn as_bool(value: &Value, op: &str) -> Result<bool, ExprError> {
+    match value {
+        Value::Bool(value) => Ok(*value),
+        other => Err(no_overload(op, &[other])),
+    }
+}
+
+fn no_overload(op: &str, args: &[&Value]) -> ExprError {
+    let types = args.iter().map(|arg| type_name(arg)).collect::<Vec<_>>();
+    ExprError::eval(format!("no such overload: {op}({})", types.join(", ")))
+}
+
+/// The expression language name of the type of `value`
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(number) if number.is_f64() => "double",
+        Value::Number(_) => "int",
+        Value::String(_) => "string",
+        Value::Array(_) => "list",
+        Value::Object(_) => "map",
+    }
+}
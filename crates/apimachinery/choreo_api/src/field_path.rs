@@ -0,0 +1,75 @@
+use std::fmt;
+
+/// PathSegment is a single step in a FieldPath.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathSegment {
+    /// A named struct field, e.g. `spec`
+    Field(String),
+    /// A position in a list, e.g. `[2]`
+    Index(usize),
+    /// A key in a map, e.g. `["tier"]`
+    Key(String),
+}
+
+/// FieldPath locates a value within an object, e.g. `spec.ports[2].name` or `spec.labels["tier"]`.
+///
+/// Validation carries the path of the value being validated down the tree, so every error
+/// reports the exact location of the offending field.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FieldPath(Vec<PathSegment>);
+
+impl FieldPath {
+    /// The path of the object being validated itself
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Returns a new path pointing to the field `name` below this path
+    pub fn field(&self, name: impl Into<String>) -> Self {
+        self.child(PathSegment::Field(name.into()))
+    }
+
+    /// Returns a new path pointing to the list item at `index` below this path
+    pub fn index(&self, index: usize) -> Self {
+        self.child(PathSegment::Index(index))
+    }
+
+    /// Returns a new path pointing to the map entry `key` below this path
+    pub fn key(&self, key: impl ToString) -> Self {
+        self.child(PathSegment::Key(key.to_string()))
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    fn child(&self, segment: PathSegment) -> Self {
+        let mut segments = self.0.clone();
+        segments.push(segment);
+        Self(segments)
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Field(name) if i == 0 => f.write_str(name)?,
+                PathSegment::Field(name) => write!(f, ".{name}")?,
+                PathSegment::Index(index) => write!(f, "[{index}]")?,
+                PathSegment::Key(key) => write!(f, "[{key:?}]")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Vec<PathSegment>> for FieldPath {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self(segments)
+    }
+}
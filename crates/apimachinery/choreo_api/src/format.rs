@@ -0,0 +1,209 @@
+//! Checks for the well known string formats supported by `#[cvalidate(format = "...")]`.
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+/// Maximum length of a DNS-1123 label
+pub const DNS1123_LABEL_MAX_LENGTH: usize = 63;
+/// Maximum length of a DNS-1123 subdomain
+pub const DNS1123_SUBDOMAIN_MAX_LENGTH: usize = 253;
+
+/// A lowercase RFC 1123 label: at most 63 lowercase alphanumeric characters or '-',
+/// starting and ending with an alphanumeric character, e.g. `my-name`.
+pub fn is_dns1123_label(value: &str) -> bool {
+    value.len() <= DNS1123_LABEL_MAX_LENGTH && is_label(value, |c| {
+        c.is_ascii_lowercase() || c.is_ascii_digit()
+    })
+}
+
+/// A lowercase RFC 1123 subdomain: one or more DNS-1123 labels separated by '.',
+/// at most 253 characters in total, e.g. `example.com`.
+pub fn is_dns1123_subdomain(value: &str) -> bool {
+    value.len() <= DNS1123_SUBDOMAIN_MAX_LENGTH && value.split('.').all(is_dns1123_label)
+}
+
+/// An IPv4 address in dotted decimal notation, e.g. `10.0.0.1`
+pub fn is_ipv4(value: &str) -> bool {
+    Ipv4Addr::from_str(value).is_ok()
+}
+
+/// An IPv6 address, e.g. `2001:db8::1`
+pub fn is_ipv6(value: &str) -> bool {
+    Ipv6Addr::from_str(value).is_ok()
+}
+
+/// An IPv4 or IPv6 prefix in CIDR notation, e.g. `10.0.0.0/8` or `2001:db8::/32`
+pub fn is_cidr(value: &str) -> bool {
+    let Some((address, prefix_length)) = value.split_once('/') else {
+        return false;
+    };
+    if prefix_length.is_empty() || !prefix_length.bytes().all(|b| b.is_ascii_digit()) {
+        return false;
+    }
+    let Ok(prefix_length) = prefix_length.parse::<u8>() else {
+        return false;
+    };
+    match IpAddr::from_str(address) {
+        Ok(IpAddr::V4(_)) => prefix_length <= 32,
+        Ok(IpAddr::V6(_)) => prefix_length <= 128,
+        Err(_) => false,
+    }
+}
+
+/// An absolute RFC 3986 URI: a scheme followed by ':' and a hierarchical part without
+/// whitespace or control characters, e.g. `https://example.com/path?query`
+pub fn is_uri(value: &str) -> bool {
+    let Some((scheme, rest)) = value.split_once(':') else {
+        return false;
+    };
+    let mut scheme_chars = scheme.chars();
+    let scheme_valid = scheme_chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && scheme_chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    scheme_valid
+        && !rest.is_empty()
+        && !rest.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// An email address of the form `local@domain`, e.g. `user@example.com`
+pub fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return false;
+    };
+    let local_valid = !local.is_empty()
+        && local.len() <= 64
+        && !local.starts_with('.')
+        && !local.ends_with('.')
+        && !local.contains("..")
+        && local.chars().all(|c| {
+            c.is_ascii_alphanumeric() || "!#$%&'*+/=?^_`{|}~.-".contains(c)
+        });
+    let domain_valid = domain.len() <= DNS1123_SUBDOMAIN_MAX_LENGTH
+        && domain.contains('.')
+        && domain.split('.').all(|label| {
+            label.len() <= DNS1123_LABEL_MAX_LENGTH && is_label(label, |c| c.is_ascii_alphanumeric())
+        });
+    local_valid && domain_valid
+}
+
+/// A UUID in its canonical hyphenated form, e.g. `123e4567-e89b-12d3-a456-426614174000`
+pub fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// An RFC 3339 date-time, e.g. `2024-01-02T15:04:05Z` or `2024-01-02T15:04:05.123+02:00`
+pub fn is_date_time(value: &str) -> bool {
+    let Some((date, time)) = value.split_once(['T', 't']) else {
+        return false;
+    };
+    is_full_date(date) && is_full_time(time)
+}
+
+/// A duration as accepted by Go's `time.ParseDuration`, e.g. `300ms`, `1h30m` or `-1.5h`
+pub fn is_duration(value: &str) -> bool {
+    let value = value.strip_prefix(['-', '+']).unwrap_or(value);
+    if value == "0" {
+        return true;
+    }
+    if value.is_empty() {
+        return false;
+    }
+
+    let mut rest = value;
+    while !rest.is_empty() {
+        // a decimal number
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number = &rest[..number_len];
+        if number.is_empty() || number == "." || number.matches('.').count() > 1 {
+            return false;
+        }
+        rest = &rest[number_len..];
+
+        // followed by a unit
+        let Some(unit) = ["ns", "us", "µs", "μs", "ms", "s", "m", "h"]
+            .into_iter()
+            .filter(|unit| rest.starts_with(unit))
+            .max_by_key(|unit| unit.len())
+        else {
+            return false;
+        };
+        rest = &rest[unit.len()..];
+    }
+    true
+}
+
+/// A label of alphanumeric characters or '-', starting and ending with an alphanumeric character.
+fn is_label(value: &str, is_alphanumeric: impl Fn(char) -> bool) -> bool {
+    !value.is_empty()
+        && !value.starts_with('-')
+        && !value.ends_with('-')
+        && value.chars().all(|c| c == '-' || is_alphanumeric(c))
+}
+
+/// `YYYY-MM-DD`
+fn is_full_date(date: &str) -> bool {
+    let Some([year, month, day]) = parse_numbers(date, '-', [4, 2, 2]) else {
+        return false;
+    };
+    let is_leap_year = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+/// `HH:MM:SS[.fraction](Z|+HH:MM|-HH:MM)`
+fn is_full_time(time: &str) -> bool {
+    let (partial_time, offset) = if let Some(partial_time) = time.strip_suffix(['Z', 'z']) {
+        (partial_time, None)
+    } else if let Some(index) = time.rfind(['+', '-']) {
+        (&time[..index], Some(&time[index + 1..]))
+    } else {
+        return false;
+    };
+
+    let (hms, fraction) = match partial_time.split_once('.') {
+        Some((hms, fraction)) => (hms, Some(fraction)),
+        None => (partial_time, None),
+    };
+    let Some([hour, minute, second]) = parse_numbers(hms, ':', [2, 2, 2]) else {
+        return false;
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+    if fraction.is_some_and(|fraction| fraction.is_empty() || !fraction.bytes().all(|b| b.is_ascii_digit())) {
+        return false;
+    }
+
+    match offset {
+        None => true,
+        Some(offset) => matches!(
+            parse_numbers(offset, ':', [2, 2]),
+            Some([hour, minute]) if hour <= 23 && minute <= 59
+        ),
+    }
+}
+
+/// Split `value` on `separator` into exactly `N` numbers with the given digit counts.
+fn parse_numbers<const N: usize>(value: &str, separator: char, digits: [usize; N]) -> Option<[u32; N]> {
+    let mut numbers = [0; N];
+    let mut parts = value.split(separator);
+    for (number, len) in numbers.iter_mut().zip(digits) {
+        let part = parts.next()?;
+        if part.len() != len || !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        *number = part.parse().ok()?;
+    }
+    parts.next().is_none().then_some(numbers)
+}
@@ -0,0 +1,91 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// IsSet reports whether a field holds a value, as used by the struct level rules
+/// `mutually_exclusive`, `required_if` and `at_least_one_of`, and by defaults with
+/// `when = "zero"`, which replace the values that are not set.
+///
+/// A field is set when it is `Some`, `true`, non-empty or non-zero. An enum deriving `ChoreoDefault`
+/// implements it when one of its variants is marked `#[cdefault]`: every other variant is set.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `choreo_api::IsSet`, so it is unknown when a `{Self}` is set",
+    label = "`{Self}` does not implement `IsSet`",
+    note = "use `Option<{Self}>`, which is set when it is `Some`",
+    note = "or implement `choreo_api::IsSet` for `{Self}`, e.g. mark the default variant of an enum deriving `ChoreoDefault` with `#[cdefault]`"
+)]
+pub trait IsSet {
+    fn is_set(&self) -> bool;
+}
+
+impl<T> IsSet for Option<T> {
+    fn is_set(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T: IsSet + ?Sized> IsSet for Box<T> {
+    fn is_set(&self) -> bool {
+        (**self).is_set()
+    }
+}
+
+impl IsSet for bool {
+    fn is_set(&self) -> bool {
+        *self
+    }
+}
+
+impl IsSet for char {
+    fn is_set(&self) -> bool {
+        *self != '\0'
+    }
+}
+
+impl IsSet for String {
+    fn is_set(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl IsSet for str {
+    fn is_set(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl IsSet for std::time::Duration {
+    fn is_set(&self) -> bool {
+        !self.is_zero()
+    }
+}
+
+impl<T> IsSet for Vec<T> {
+    fn is_set(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<K, V, S> IsSet for HashMap<K, V, S> {
+    fn is_set(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<K, V> IsSet for BTreeMap<K, V> {
+    fn is_set(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+macro_rules! impl_is_set_for_numbers {
+    ($($t:ty),*) => {
+        $(
+            impl IsSet for $t {
+                fn is_set(&self) -> bool {
+                    *self != 0 as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_is_set_for_numbers!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
@@ -1,5 +1,26 @@
 pub mod default;
-pub use self::default::Defaultable;
+pub use self::default::{DefaultContext, DefaultedField, Defaultable, DefaultingReport};
 
 pub mod validate;
-pub use self::validate::Validate;
\ No newline at end of file
+pub use self::validate::Validate;
+
+pub mod validation_error;
+pub use self::validation_error::{
+    Severity, ValidationError, ValidationErrorKind, ValidationErrors, ValidationReport,
+};
+
+pub mod field_path;
+pub use self::field_path::{FieldPath, PathSegment};
+
+pub mod format;
+
+pub mod is_set;
+pub use self::is_set::IsSet;
+
+pub mod unique;
+
+pub mod rule;
+pub use self::rule::Rule;
+
+pub mod expr;
+pub use self::expr::ExpressionRule;
@@ -0,0 +1,33 @@
+/// Rule is a reusable validation rule defined outside of the derive, e.g. in a crate of shared rules.
+///
+/// A rule is referenced by an expression evaluating to it, so it may take parameters:
+/// `#[cvalidate(custom = Semver)]` or `#[cvalidate(custom = Quantity::at_most("10Gi"))]`.
+/// `T` is the type of the field, the inner type for `Option` fields and the element type
+/// within `items(...)`, `keys(...)` and `values(...)`.
+pub trait Rule<T: ?Sized> {
+    /// Name reported as the violated constraint, e.g. `semver`
+    fn name(&self) -> &str;
+
+    /// Check `value`, returning the reason when it is invalid
+    fn check(&self, value: &T) -> Result<(), String>;
+}
+
+impl<T: ?Sized, R: Rule<T> + ?Sized> Rule<T> for &R {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn check(&self, value: &T) -> Result<(), String> {
+        (**self).check(value)
+    }
+}
+
+impl<T: ?Sized, R: Rule<T> + ?Sized> Rule<T> for Box<R> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn check(&self, value: &T) -> Result<(), String> {
+        (**self).check(value)
+    }
+}
@@ -0,0 +1,53 @@
+//! Finding the duplicate items of a list for the `unique_items` and `list_map_keys` rules.
+//!
+//! Items implementing `Hash` and `Eq` are found with a single pass over the list, items that only
+//! implement `PartialEq`, e.g. floats, are compared pairwise. The derive calls
+//! `(&Duplicates(items)).find()` with both traits in scope, which resolves to [`HashedDuplicates`]
+//! when the items implement `Hash` and `Eq`, and to [`ComparedDuplicates`] otherwise.
+use std::collections::hash_map::{Entry, HashMap};
+use std::hash::Hash;
+
+/// Duplicates wraps the items of a list, or the keys of its items.
+pub struct Duplicates<'a, T>(pub &'a [T]);
+
+/// Find the duplicates by hashing the items.
+pub trait HashedDuplicates {
+    /// Returns the index of every item equal to an earlier item, with the index of the first of them
+    fn find(&self) -> Vec<(usize, usize)>;
+}
+
+impl<T: Hash + Eq> HashedDuplicates for Duplicates<'_, T> {
+    fn find(&self) -> Vec<(usize, usize)> {
+        let mut first_index = HashMap::with_capacity(self.0.len());
+        let mut duplicates = Vec::new();
+        for (index, item) in self.0.iter().enumerate() {
+            match first_index.entry(item) {
+                Entry::Occupied(first) => duplicates.push((index, *first.get())),
+                Entry::Vacant(entry) => {
+                    entry.insert(index);
+                }
+            }
+        }
+        duplicates
+    }
+}
+
+/// Find the duplicates by comparing every item to the items before it.
+pub trait ComparedDuplicates {
+    /// Returns the index of every item equal to an earlier item, with the index of the first of them
+    fn find(&self) -> Vec<(usize, usize)>;
+}
+
+impl<T: PartialEq> ComparedDuplicates for &Duplicates<'_, T> {
+    fn find(&self) -> Vec<(usize, usize)> {
+        let items = self.0;
+        items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                let first = items[..index].iter().position(|other| other == item)?;
+                Some((index, first))
+            })
+            .collect()
+    }
+}
@@ -1,3 +1,145 @@
+use crate::{FieldPath, ValidationErrors, ValidationReport};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::hash::{BuildHasher, Hash};
+use std::num::{
+    NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+};
+
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` does not implement `Validate`",
+    label = "fields are validated through their `Validate` implementation",
+    note = "derive `Validate` for `{Self}`, or exclude the field with `#[cvalidate(skip)]`"
+)]
 pub trait Validate {
-    fn validate(&self) -> Result<(), String>;
-}
\ No newline at end of file
+    /// Validate the object located at `path`, recording every violation in `errors`.
+    fn validate_at(&self, path: &FieldPath, errors: &mut ValidationErrors);
+
+    /// Validate the transition from `old` to `self` for the object located at `path`,
+    /// e.g. that immutable fields did not change. Only the rules comparing both versions
+    /// are checked here, `validate_at` checks the new object on its own.
+    fn validate_transition_at(&self, _old: &Self, _path: &FieldPath, _errors: &mut ValidationErrors) {}
+
+    /// Validate the object, returning all errors with paths relative to the object itself.
+    /// Warnings do not fail the validation, see [`Validate::report`].
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        self.report().into_result().map(|_warnings| ())
+    }
+
+    /// Validate an update from `old` to `self`: the new object itself and the transition from `old`.
+    fn validate_update(&self, old: &Self) -> Result<(), ValidationErrors> {
+        self.report_update(old).into_result().map(|_warnings| ())
+    }
+
+    /// Validate the object, separating the errors from the warnings.
+    fn report(&self) -> ValidationReport {
+        let mut errors = ValidationErrors::new();
+        self.validate_at(&FieldPath::root(), &mut errors);
+        errors.into_report()
+    }
+
+    /// Validate an update from `old` to `self`, separating the errors from the warnings.
+    fn report_update(&self, old: &Self) -> ValidationReport {
+        let mut errors = ValidationErrors::new();
+        self.validate_at(&FieldPath::root(), &mut errors);
+        self.validate_transition_at(old, &FieldPath::root(), &mut errors);
+        errors.into_report()
+    }
+}
+
+/// The value is validated when it is set, transitions only when both versions are set.
+impl<T: Validate> Validate for Option<T> {
+    fn validate_at(&self, path: &FieldPath, errors: &mut ValidationErrors) {
+        if let Some(value) = self {
+            value.validate_at(path, errors);
+        }
+    }
+
+    fn validate_transition_at(&self, old: &Self, path: &FieldPath, errors: &mut ValidationErrors) {
+        if let (Some(value), Some(old)) = (self, old) {
+            value.validate_transition_at(old, path, errors);
+        }
+    }
+}
+
+/// Items are located at their index. Their transitions are not checked since items can not be
+/// matched between two versions, `list_map_keys` matches them by their key fields.
+impl<T: Validate> Validate for Vec<T> {
+    fn validate_at(&self, path: &FieldPath, errors: &mut ValidationErrors) {
+        for (index, item) in self.iter().enumerate() {
+            item.validate_at(&path.index(index), errors);
+        }
+    }
+}
+
+/// Values are located at their key, transitions are checked for the keys present in both versions.
+impl<K, V, S> Validate for HashMap<K, V, S>
+where
+    K: Eq + Hash + Display,
+    V: Validate,
+    S: BuildHasher,
+{
+    fn validate_at(&self, path: &FieldPath, errors: &mut ValidationErrors) {
+        for (key, value) in self {
+            value.validate_at(&path.key(key), errors);
+        }
+    }
+
+    fn validate_transition_at(&self, old: &Self, path: &FieldPath, errors: &mut ValidationErrors) {
+        for (key, value) in self {
+            if let Some(old_value) = old.get(key) {
+                value.validate_transition_at(old_value, &path.key(key), errors);
+            }
+        }
+    }
+}
+
+/// Values are located at their key, transitions are checked for the keys present in both versions.
+impl<K: Ord + Display, V: Validate> Validate for BTreeMap<K, V> {
+    fn validate_at(&self, path: &FieldPath, errors: &mut ValidationErrors) {
+        for (key, value) in self {
+            value.validate_at(&path.key(key), errors);
+        }
+    }
+
+    fn validate_transition_at(&self, old: &Self, path: &FieldPath, errors: &mut ValidationErrors) {
+        for (key, value) in self {
+            if let Some(old_value) = old.get(key) {
+                value.validate_transition_at(old_value, &path.key(key), errors);
+            }
+        }
+    }
+}
+
+impl<T: Validate + ?Sized> Validate for Box<T> {
+    fn validate_at(&self, path: &FieldPath, errors: &mut ValidationErrors) {
+        (**self).validate_at(path, errors);
+    }
+
+    fn validate_transition_at(&self, old: &Self, path: &FieldPath, errors: &mut ValidationErrors) {
+        (**self).validate_transition_at(old, path, errors);
+    }
+}
+
+/// Primitives are valid on their own, their rules are declared on the field holding them.
+macro_rules! impl_validate_for_primitives {
+    ($($typ:ty),* $(,)?) => {
+        $(
+            impl Validate for $typ {
+                fn validate_at(&self, _path: &FieldPath, _errors: &mut ValidationErrors) {}
+            }
+        )*
+    };
+}
+
+impl_validate_for_primitives!(
+    bool, char, String, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64,
+    NonZeroI8, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI128, NonZeroIsize, NonZeroU8,
+    NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU128, NonZeroUsize, std::time::Duration,
+);
+
+/// A point in time is a single value like the primitives.
+impl<Tz: chrono::TimeZone> Validate for chrono::DateTime<Tz> {
+    fn validate_at(&self, _path: &FieldPath, _errors: &mut ValidationErrors) {}
+}
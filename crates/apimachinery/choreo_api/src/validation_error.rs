@@ -0,0 +1,439 @@
+use crate::FieldPath;
+use std::fmt;
+
+/// ValidationErrorKind is the stable, machine readable category of a validation failure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValidationErrorKind {
+    /// A required field is missing
+    Required,
+    /// A string is shorter than its minimum length
+    TooShort,
+    /// A string is longer than its maximum length
+    TooLong,
+    /// A collection has fewer items than its minimum
+    TooFewItems,
+    /// A collection has more items than its maximum
+    TooManyItems,
+    /// A string does not match the required pattern
+    PatternMismatch,
+    /// A number is outside of its allowed range
+    OutOfRange,
+    /// A number is not a multiple of the required value
+    NotMultipleOf,
+    /// A value is not one of the allowed values
+    NotOneOf,
+    /// A list item is equal to an earlier item, or has the same keys
+    Duplicate,
+    /// A string is not valid for its well known format, e.g. `ipv4`
+    InvalidFormat,
+    /// A field is set together with a field it is mutually exclusive with
+    Conflict,
+    /// A custom validation function failed
+    Custom,
+    /// An expression rule evaluated to `false` or could not be evaluated
+    Rule,
+    /// A deprecated field is set
+    Deprecated,
+    /// An immutable field was changed by an update
+    Immutable,
+    /// A field was changed by an update in a way that is not allowed, e.g. decreased
+    InvalidTransition,
+}
+
+impl ValidationErrorKind {
+    /// Returns the stable identifier of the kind, e.g. `too_long`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Required => "required",
+            Self::TooShort => "too_short",
+            Self::TooLong => "too_long",
+            Self::TooFewItems => "too_few_items",
+            Self::TooManyItems => "too_many_items",
+            Self::PatternMismatch => "pattern_mismatch",
+            Self::OutOfRange => "out_of_range",
+            Self::NotMultipleOf => "not_multiple_of",
+            Self::NotOneOf => "not_one_of",
+            Self::Duplicate => "duplicate",
+            Self::InvalidFormat => "invalid_format",
+            Self::Conflict => "conflict",
+            Self::Custom => "custom",
+            Self::Rule => "rule",
+            Self::Deprecated => "deprecated",
+            Self::Immutable => "immutable",
+            Self::InvalidTransition => "invalid_transition",
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Severity decides whether a violation rejects the object or is only reported as a warning.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Severity {
+    /// The object is invalid
+    #[default]
+    Error,
+    /// The object is valid, the violation is surfaced to the client, e.g. a deprecated field
+    Warning,
+}
+
+impl Severity {
+    /// Returns the stable identifier of the severity, e.g. `warning`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// ValidationError describes a single violated validation rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    /// Path of the offending field, e.g. `spec.replicas`
+    pub path: FieldPath,
+    /// Category of the failure
+    pub kind: ValidationErrorKind,
+    /// Whether the violation rejects the object, chosen with `warn` on the rule
+    pub severity: Severity,
+    /// The constraint that was violated, e.g. `>= 3` or the regex pattern
+    pub constraint: Option<String>,
+    /// The offending value, if it can be represented
+    pub value: Option<String>,
+    /// Additional detail, e.g. the error returned by a custom validation function
+    pub message: Option<String>,
+    /// Stable code chosen with `code = "..."` on the rule, see [`ValidationError::code`]
+    pub code: Option<String>,
+    /// Message chosen with `message = "..."` on the rule, replaces the default text when displayed.
+    /// It may refer to the placeholders in [`TEMPLATE_PLACEHOLDERS`], e.g. `{path}`
+    pub template: Option<String>,
+}
+
+/// Placeholders a message template is rendered with when the error is displayed.
+/// `{limit}` is replaced when the rule is expanded, e.g. by `3` for `min_length = 3`.
+pub const TEMPLATE_PLACEHOLDERS: [&str; 5] = ["path", "value", "constraint", "message", "code"];
+
+impl ValidationError {
+    /// Construct an error of the given kind for the field at `path`
+    pub fn new(path: FieldPath, kind: ValidationErrorKind) -> Self {
+        Self {
+            path,
+            kind,
+            severity: Severity::Error,
+            constraint: None,
+            value: None,
+            message: None,
+            code: None,
+            template: None,
+        }
+    }
+
+    /// Returns the code chosen for the rule, or the stable identifier of the kind, e.g. `too_long`
+    pub fn code(&self) -> &str {
+        self.code.as_deref().unwrap_or(self.kind.as_str())
+    }
+
+    /// Set the violated constraint
+    pub fn with_constraint(mut self, constraint: impl Into<String>) -> Self {
+        self.constraint = Some(constraint.into());
+        self
+    }
+
+    /// Set the offending value
+    pub fn with_value(mut self, value: impl ToString) -> Self {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    /// Set an additional detail message
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Set the severity of the violation
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Whether the violation is only a warning
+    pub fn is_warning(&self) -> bool {
+        self.severity == Severity::Warning
+    }
+
+    /// Set the code reported instead of the kind
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.code = Some(code.into());
+        self
+    }
+
+    /// Set the message template that replaces the default text
+    pub fn with_template(mut self, template: impl Into<String>) -> Self {
+        self.template = Some(template.into());
+        self
+    }
+
+    fn placeholder(&self, name: &str) -> Option<String> {
+        match name {
+            "path" => Some(self.path.to_string()),
+            "value" => Some(self.value.clone().unwrap_or_default()),
+            "constraint" => Some(self.constraint.clone().unwrap_or_default()),
+            "message" => Some(self.message.clone().unwrap_or_default()),
+            "code" => Some(self.code().to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Render a message `template`, replacing every `{name}` placeholder by `resolve(name)`.
+/// `{{` and `}}` are literal braces. Fails when a placeholder can not be resolved or a brace is not matched.
+pub fn render_template(
+    template: &str,
+    mut resolve: impl FnMut(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.as_str().starts_with('{') => {
+                chars.next();
+                rendered.push('{');
+            }
+            '}' if chars.as_str().starts_with('}') => {
+                chars.next();
+                rendered.push('}');
+            }
+            '{' => {
+                let rest = chars.as_str();
+                let Some(end) = rest.find('}') else {
+                    return Err("unmatched `{` in message template".to_string());
+                };
+                let name = &rest[..end];
+                match resolve(name) {
+                    Some(value) => rendered.push_str(&value),
+                    None => {
+                        return Err(format!(
+                            "unknown placeholder `{{{name}}}` in message template"
+                        ))
+                    }
+                }
+                chars = rest[end + 1..].chars();
+            }
+            '}' => return Err("unmatched `}` in message template".to_string()),
+            c => rendered.push(c),
+        }
+    }
+    Ok(rendered)
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(template) = &self.template {
+            // Templates are checked when the rule is expanded, fall back to the raw text otherwise
+            return match render_template(template, |name| self.placeholder(name)) {
+                Ok(message) => f.write_str(&message),
+                Err(_) => f.write_str(template),
+            };
+        }
+        let path = &self.path;
+        let constraint = self.constraint.as_deref().unwrap_or_default();
+        match self.kind {
+            ValidationErrorKind::Required => match &self.constraint {
+                Some(condition) => write!(f, "Field '{path}' is required when {condition}"),
+                None => write!(f, "Field '{path}' is required"),
+            },
+            ValidationErrorKind::TooShort
+            | ValidationErrorKind::TooLong
+            | ValidationErrorKind::TooFewItems
+            | ValidationErrorKind::TooManyItems => {
+                write!(f, "Field '{path}' length must be {constraint}.")
+            }
+            ValidationErrorKind::PatternMismatch => write!(
+                f,
+                "Field '{path}' does not match the required pattern: '{constraint}'."
+            ),
+            ValidationErrorKind::OutOfRange => write!(f, "Field '{path}' must be {constraint}."),
+            ValidationErrorKind::NotMultipleOf => {
+                write!(f, "Field '{path}' must be a multiple of {constraint}.")
+            }
+            ValidationErrorKind::NotOneOf => {
+                write!(f, "Field '{path}' must be one of {constraint}.")
+            }
+            ValidationErrorKind::Duplicate => {
+                write!(f, "Field '{path}' is a duplicate of {constraint}")?;
+                match &self.message {
+                    Some(message) => write!(f, ": {message}."),
+                    None => f.write_str("."),
+                }
+            }
+            ValidationErrorKind::InvalidFormat => {
+                write!(f, "Field '{path}' must be a valid {constraint}.")
+            }
+            ValidationErrorKind::Conflict => {
+                write!(
+                    f,
+                    "Field '{path}' must not be set together with {constraint}."
+                )
+            }
+            ValidationErrorKind::Custom => write!(
+                f,
+                "Field '{path}' failed custom validation '{constraint}': {}",
+                self.message.as_deref().unwrap_or_default()
+            ),
+            ValidationErrorKind::Rule => {
+                if path.is_root() {
+                    write!(f, "Object failed rule '{constraint}'")?;
+                } else {
+                    write!(f, "Field '{path}' failed rule '{constraint}'")?;
+                }
+                match &self.message {
+                    Some(message) => write!(f, ": {message}"),
+                    None => f.write_str("."),
+                }
+            }
+            ValidationErrorKind::Deprecated => {
+                write!(f, "Field '{path}' is deprecated")?;
+                match &self.message {
+                    Some(message) => write!(f, ": {message}."),
+                    None => f.write_str("."),
+                }
+            }
+            ValidationErrorKind::Immutable => match &self.constraint {
+                Some(condition) => write!(f, "Field '{path}' is immutable {condition}."),
+                None => write!(f, "Field '{path}' is immutable."),
+            },
+            ValidationErrorKind::InvalidTransition => {
+                write!(f, "Field '{path}' {constraint}.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// ValidationErrors collects all rule violations found while validating an object,
+/// warnings included. [`ValidationErrors::into_report`] separates them from the errors.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<ValidationError>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a validation error
+    pub fn push(&mut self, error: ValidationError) {
+        self.0.push(error);
+    }
+
+    /// Record all errors of another collection
+    pub fn extend(&mut self, other: ValidationErrors) {
+        self.0.extend(other.0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, ValidationError> {
+        self.0.iter()
+    }
+
+    /// The paths of the recorded errors, in the order they were recorded
+    pub fn paths(&self) -> impl Iterator<Item = &FieldPath> {
+        self.0.iter().map(|error| &error.path)
+    }
+
+    /// Whether any violation with severity `Error` was recorded
+    pub fn has_errors(&self) -> bool {
+        self.0.iter().any(|error| !error.is_warning())
+    }
+
+    /// Returns `Ok(())` when no errors were recorded, otherwise `Err` with the errors.
+    /// Warnings are dropped, use [`ValidationErrors::into_report`] to keep them.
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        self.into_report().into_result().map(|_warnings| ())
+    }
+
+    /// Separate the errors from the warnings
+    pub fn into_report(self) -> ValidationReport {
+        let (warnings, errors) = self.0.into_iter().partition(ValidationError::is_warning);
+        ValidationReport {
+            errors: Self(errors),
+            warnings: Self(warnings),
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl IntoIterator for ValidationErrors {
+    type Item = ValidationError;
+    type IntoIter = std::vec::IntoIter<ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// ValidationReport separates the violations that reject an object from the warnings that do not,
+/// so a caller can accept an object and still surface its warnings.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// Violations with severity `Error`
+    pub errors: ValidationErrors,
+    /// Violations with severity `Warning`
+    pub warnings: ValidationErrors,
+}
+
+impl ValidationReport {
+    /// Whether the object is accepted, i.e. no errors were recorded
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns `Ok` with the warnings when the object is accepted, otherwise `Err` with the errors
+    pub fn into_result(self) -> Result<ValidationErrors, ValidationErrors> {
+        if self.is_valid() {
+            Ok(self.warnings)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a ValidationErrors {
+    type Item = &'a ValidationError;
+    type IntoIter = std::slice::Iter<'a, ValidationError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
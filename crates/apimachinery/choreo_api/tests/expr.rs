@@ -0,0 +1,269 @@
+use choreo_api::expr::{ExprError, Program};
+use choreo_api::{ExpressionRule, FieldPath, ValidationErrors};
+use serde_json::{json, Value};
+
+fn eval(source: &str, value: Value) -> Result<Value, ExprError> {
+    Program::compile(source)?.evaluate(&value)
+}
+
+fn compile_error(source: &str) -> ExprError {
+    Program::compile(source).expect_err(source)
+}
+
+#[test]
+fn precedence() {
+    let cases = [
+        ("1 + 2 * 3", json!(7)),
+        ("(1 + 2) * 3", json!(9)),
+        ("10 - 4 - 3", json!(3)),
+        ("7 % 4 * 2", json!(6)),
+        ("-2 * 3 + 1", json!(-5)),
+        ("1 + 2 < 4 && 3 == 3", json!(true)),
+        ("true || false && false", json!(true)),
+        ("!false && false", json!(false)),
+        ("1 < 2 ? 'a' : 'b'", json!("a")),
+        ("false ? 1 : true ? 2 : 3", json!(2)),
+        ("2 in [1, 2] == true", json!(true)),
+    ];
+    for (source, expected) in cases {
+        assert_eq!(eval(source, Value::Null), Ok(expected), "{source}");
+    }
+}
+
+#[test]
+fn select_and_index() {
+    let value = json!({
+        "spec": {"replicas": 3, "ports": [80, 443], "labels": {"tier": "web"}},
+    });
+    assert_eq!(eval("self.spec.replicas", value.clone()), Ok(json!(3)));
+    assert_eq!(eval("self.spec.ports[1]", value.clone()), Ok(json!(443)));
+    assert_eq!(
+        eval("self.spec.labels['tier']", value.clone()),
+        Ok(json!("web"))
+    );
+    assert_eq!(eval("self.spec", value.clone()), Ok(value["spec"].clone()));
+    assert_eq!(eval("[10, 20][1]", Value::Null), Ok(json!(20)));
+    assert_eq!(
+        eval("self.spec.missing", value.clone())
+            .unwrap_err()
+            .message,
+        "no such key: missing"
+    );
+    assert_eq!(
+        eval("self.spec.ports[2]", value.clone())
+            .unwrap_err()
+            .message,
+        "index out of bounds: 2"
+    );
+    assert_eq!(
+        eval("self.spec.replicas.value", value).unwrap_err().message,
+        "cannot select field 'value' from int"
+    );
+}
+
+#[test]
+fn has() {
+    let value = json!({"name": "web", "port": null});
+    assert_eq!(eval("has(self.name)", value.clone()), Ok(json!(true)));
+    assert_eq!(eval("has(self.port)", value.clone()), Ok(json!(false)));
+    assert_eq!(eval("has(self.missing)", value.clone()), Ok(json!(false)));
+    assert_eq!(
+        eval("!has(self.port) || self.port > 0", value),
+        Ok(json!(true))
+    );
+    assert_eq!(
+        compile_error("has(self)").message,
+        "has() requires a field selection, e.g. has(self.field)"
+    );
+}
+
+#[test]
+fn size() {
+    let value = json!({"name": "héllo", "ports": [1, 2, 3], "labels": {"a": "1"}});
+    assert_eq!(eval("size(self.name)", value.clone()), Ok(json!(5)));
+    assert_eq!(eval("self.ports.size()", value.clone()), Ok(json!(3)));
+    assert_eq!(eval("size(self.labels)", value.clone()), Ok(json!(1)));
+    assert_eq!(
+        eval("size(self.ports[0])", value).unwrap_err().message,
+        "no such overload: size(int)"
+    );
+}
+
+#[test]
+fn functions_and_macros() {
+    let value = json!({"ports": [80, 443, 8080], "labels": {"app": "web", "tier": "db"}});
+    assert_eq!(
+        eval("self.ports.all(p, p > 0)", value.clone()),
+        Ok(json!(true))
+    );
+    assert_eq!(
+        eval("self.ports.exists(p, p == 443)", value.clone()),
+        Ok(json!(true))
+    );
+    assert_eq!(
+        eval("self.ports.exists_one(p, p > 100)", value.clone()),
+        Ok(json!(false))
+    );
+    assert_eq!(
+        eval("self.ports.map(p, p * 2)", value.clone()),
+        Ok(json!([160, 886, 16160]))
+    );
+    assert_eq!(
+        eval("self.ports.filter(p, p < 1000)", value.clone()),
+        Ok(json!([80, 443]))
+    );
+    assert_eq!(
+        eval("self.labels.all(k, k.size() > 2)", value.clone()),
+        Ok(json!(true))
+    );
+    assert_eq!(
+        eval("[1, 2, 3].map(x, x + 1).filter(x, x > 2)", Value::Null),
+        Ok(json!([3, 4]))
+    );
+    assert_eq!(
+        eval("'web-1'.startsWith('web')", Value::Null),
+        Ok(json!(true))
+    );
+    assert_eq!(eval("int('42') + int(1.9)", Value::Null), Ok(json!(43)));
+    assert_eq!(eval("string(1) + 'x'", Value::Null), Ok(json!("1x")));
+    assert_eq!(
+        compile_error("self.ports.all(p, q > 0)").message,
+        "undeclared reference to 'q'"
+    );
+}
+
+#[test]
+fn old_self() {
+    let program = Program::compile("self.replicas >= oldSelf.replicas").unwrap();
+    assert!(program.is_transition());
+    assert_eq!(
+        program.evaluate_transition(&json!({"replicas": 3}), &json!({"replicas": 2})),
+        Ok(json!(true))
+    );
+    assert_eq!(
+        program.evaluate_transition(&json!({"replicas": 1}), &json!({"replicas": 2})),
+        Ok(json!(false))
+    );
+    assert!(!Program::compile("self.replicas > 0")
+        .unwrap()
+        .is_transition());
+}
+
+#[test]
+fn type_errors() {
+    let cases = [
+        ("1 + 'a'", "no such overload: +(int, string)"),
+        ("!1", "no such overload: !(int)"),
+        ("-'a'", "no such overload: -(string)"),
+        ("1 && true", "no such overload: &&(int)"),
+        ("1 < 'a'", "no such overload: <(int, string)"),
+        ("1 ? 2 : 3", "no such overload: ?:(int)"),
+        ("1 / 0", "division by zero"),
+        ("[1].all(x, x)", "no such overload: all(int)"),
+    ];
+    for (source, message) in cases {
+        assert_eq!(
+            eval(source, Value::Null).unwrap_err().message,
+            message,
+            "{source}"
+        );
+    }
+}
+
+#[test]
+fn short_circuit() {
+    assert_eq!(eval("false && 1 / 0 == 1", Value::Null), Ok(json!(false)));
+    assert_eq!(eval("true || self.missing", json!({})), Ok(json!(true)));
+    assert_eq!(eval("true ? 1 : 1 / 0", Value::Null), Ok(json!(1)));
+}
+
+#[test]
+fn malformed() {
+    let cases = [
+        ("1 +", "unexpected end of expression", 2),
+        ("(1 + 2", "unexpected end of expression", 5),
+        ("1 2", "unexpected '2'", 2),
+        ("'open", "unterminated string literal", 0),
+        ("1 # 2", "unexpected character '#'", 2),
+        ("self.", "unexpected end of expression", 4),
+        ("unknown(1)", "unknown function 'unknown'", 0),
+        ("self.name.upper()", "unknown method 'upper'", 10),
+        ("size(1, 2)", "wrong number of arguments for 'size'", 0),
+        ("other > 1", "undeclared reference to 'other'", 0),
+    ];
+    for (source, message, position) in cases {
+        let error = compile_error(source);
+        assert_eq!(
+            (error.message.as_str(), error.position),
+            (message, Some(position)),
+            "{source}"
+        );
+    }
+    assert_eq!(
+        compile_error("1 +").to_string(),
+        "unexpected end of expression at position 2"
+    );
+}
+
+#[test]
+fn nesting_limit() {
+    let too_deep = "expression exceeds the maximum nesting depth of 100";
+    let cases = [
+        format!("{}1{}", "(".repeat(10_000), ")".repeat(10_000)),
+        format!("{}true", "!".repeat(10_000)),
+        format!("self{}", ".a".repeat(10_000)),
+        format!("1{}", " + 1".repeat(10_000)),
+        format!("{}1{}", "[".repeat(10_000), "]".repeat(10_000)),
+        format!("{}1{}", "size(".repeat(10_000), ")".repeat(10_000)),
+    ];
+    for source in &cases {
+        assert_eq!(compile_error(source).message, too_deep, "{}", &source[..20]);
+        assert!(ExpressionRule::new(source).is_err());
+    }
+
+    // Expressions below the limit compile and evaluate
+    let source = format!("{}true", "!".repeat(98));
+    assert_eq!(eval(&source, Value::Null), Ok(json!(true)));
+    let source = format!("{}1{}", "(".repeat(90), ")".repeat(90));
+    assert_eq!(eval(&source, Value::Null), Ok(json!(1)));
+    let mut value = json!(1);
+    for _ in 0..90 {
+        value = json!({ "a": value });
+    }
+    let source = format!("self{}", ".a".repeat(90));
+    assert_eq!(eval(&source, value), Ok(json!(1)));
+}
+
+#[test]
+fn expression_rule() {
+    let rule = ExpressionRule::new("self.min <= self.max")
+        .unwrap()
+        .with_message("{path}: min must not exceed max");
+    let path = FieldPath::root().field("spec");
+
+    let mut errors = ValidationErrors::new();
+    rule.validate_at(&json!({"min": 1, "max": 2}), &path, &mut errors);
+    assert!(errors.is_empty());
+
+    rule.validate_at(&json!({"min": 3, "max": 2}), &path, &mut errors);
+    let error = errors.iter().next().unwrap();
+    assert_eq!(error.path.to_string(), "spec");
+    assert_eq!(error.constraint.as_deref(), Some("self.min <= self.max"));
+    assert_eq!(error.to_string(), "spec: min must not exceed max");
+
+    let mut errors = ValidationErrors::new();
+    ExpressionRule::new("self.min")
+        .unwrap()
+        .validate_at(&json!({"min": 1}), &path, &mut errors);
+    assert_eq!(
+        errors.iter().next().unwrap().message.as_deref(),
+        Some("rule must evaluate to a bool, got int")
+    );
+
+    let transition = ExpressionRule::new("self == oldSelf").unwrap();
+    let mut errors = ValidationErrors::new();
+    transition.validate_at(&json!(1), &path, &mut errors);
+    assert!(errors.is_empty());
+    transition.validate_transition_at(&json!(1), &json!(2), &path, &mut errors);
+    assert_eq!(errors.len(), 1);
+}
@@ -0,0 +1,74 @@
+use choreo_api::{
+    FieldPath, PathSegment, Validate, ValidationError, ValidationErrorKind, ValidationErrors,
+};
+use std::collections::BTreeMap;
+
+#[test]
+fn display() {
+    let root = FieldPath::root();
+    assert!(root.is_root());
+    assert_eq!(root.to_string(), "");
+
+    let path = root.field("spec").field("ports").index(2).field("name");
+    assert_eq!(path.to_string(), "spec.ports[2].name");
+    assert!(!path.is_root());
+
+    let path = root
+        .field("spec")
+        .field("labels")
+        .key("app.kubernetes.io/name");
+    assert_eq!(path.to_string(), r#"spec.labels["app.kubernetes.io/name"]"#);
+    assert_eq!(root.index(0).field("name").to_string(), "[0].name");
+}
+
+#[test]
+fn segments() {
+    let path = FieldPath::root().field("spec").index(1).key(7);
+    assert_eq!(
+        path.segments(),
+        [
+            PathSegment::Field("spec".to_string()),
+            PathSegment::Index(1),
+            PathSegment::Key("7".to_string()),
+        ]
+    );
+    assert_eq!(FieldPath::from(path.segments().to_vec()), path);
+}
+
+#[test]
+fn child_paths_do_not_change_the_parent() {
+    let spec = FieldPath::root().field("spec");
+    let _ = spec.field("replicas");
+    assert_eq!(spec.to_string(), "spec");
+}
+
+struct Positive(i32);
+
+impl Validate for Positive {
+    fn validate_at(&self, path: &FieldPath, errors: &mut ValidationErrors) {
+        if self.0 <= 0 {
+            errors.push(ValidationError::new(
+                path.clone(),
+                ValidationErrorKind::OutOfRange,
+            ));
+        }
+    }
+}
+
+#[test]
+fn collections_extend_the_path() {
+    let path = FieldPath::root().field("spec");
+    let mut errors = ValidationErrors::new();
+    vec![Positive(1), Positive(0)].validate_at(&path.field("items"), &mut errors);
+    Some(Positive(-1)).validate_at(&path.field("optional"), &mut errors);
+    BTreeMap::from([("b", Positive(0)), ("a", Positive(2))])
+        .validate_at(&path.field("map"), &mut errors);
+    let paths = errors
+        .iter()
+        .map(|error| error.path.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        ["spec.items[1]", "spec.optional", r#"spec.map["b"]"#]
+    );
+}
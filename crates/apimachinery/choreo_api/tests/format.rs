@@ -0,0 +1,130 @@
+use choreo_api::format::*;
+
+fn check(is_valid: fn(&str) -> bool, valid: &[&str], invalid: &[&str]) {
+    for value in valid {
+        assert!(is_valid(value), "{value:?} should be valid");
+    }
+    for value in invalid {
+        assert!(!is_valid(value), "{value:?} should be invalid");
+    }
+}
+
+#[test]
+fn dns1123() {
+    let label = "a".repeat(DNS1123_LABEL_MAX_LENGTH);
+    let too_long = "a".repeat(DNS1123_LABEL_MAX_LENGTH + 1);
+    check(
+        is_dns1123_label,
+        &["a", "my-name", "0abc", &label],
+        &["", "-a", "a-", "My-name", "a.b", "a_b", &too_long],
+    );
+    let subdomain = [label.as_str(); 3].join(".");
+    let too_long = [label.as_str(); 4].join(".");
+    check(
+        is_dns1123_subdomain,
+        &["example.com", "a", "my-app.prod.svc", &subdomain],
+        &["", "example..com", ".example.com", "Example.com", &too_long],
+    );
+}
+
+#[test]
+fn ip_and_cidr() {
+    check(
+        is_ipv4,
+        &["10.0.0.1", "0.0.0.0"],
+        &["10.0.0", "256.0.0.1", "::1"],
+    );
+    check(
+        is_ipv6,
+        &["2001:db8::1", "::1", "::"],
+        &["10.0.0.1", "2001:db8:::1"],
+    );
+    check(
+        is_cidr,
+        &["10.0.0.0/8", "0.0.0.0/0", "2001:db8::/32", "::/128"],
+        &[
+            "10.0.0.0",
+            "10.0.0.0/33",
+            "::/129",
+            "10.0.0.0/",
+            "10.0.0.0/+8",
+            "a/8",
+        ],
+    );
+}
+
+#[test]
+fn uri_and_email() {
+    check(
+        is_uri,
+        &[
+            "https://example.com/path?query",
+            "urn:isbn:0451450523",
+            "git+ssh://host/repo",
+        ],
+        &[
+            "example.com",
+            "1http://example.com",
+            "https:",
+            "https://exa mple.com",
+        ],
+    );
+    check(
+        is_email,
+        &["user@example.com", "first.last+tag@mail.example.org"],
+        &[
+            "user",
+            "@example.com",
+            "user@localhost",
+            ".user@example.com",
+            "a..b@example.com",
+            "user@-example.com",
+        ],
+    );
+}
+
+#[test]
+fn uuid() {
+    check(
+        is_uuid,
+        &[
+            "123e4567-e89b-12d3-a456-426614174000",
+            "123E4567-E89B-12D3-A456-426614174000",
+        ],
+        &[
+            "123e4567e89b12d3a456426614174000",
+            "123e4567-e89b-12d3-a456-42661417400g",
+            "123e4567-e89b-12d3-a456",
+        ],
+    );
+}
+
+#[test]
+fn date_time() {
+    check(
+        is_date_time,
+        &[
+            "2024-01-02T15:04:05Z",
+            "2024-01-02T15:04:05.123+02:00",
+            "2024-02-29t00:00:00z",
+            "2024-12-31T23:59:60Z",
+        ],
+        &[
+            "2024-01-02",
+            "2024-01-02 15:04:05Z",
+            "2023-02-29T00:00:00Z",
+            "2024-13-01T00:00:00Z",
+            "2024-01-02T24:00:00Z",
+            "2024-01-02T15:04:05",
+        ],
+    );
+}
+
+#[test]
+fn duration() {
+    check(
+        is_duration,
+        &["0", "300ms", "1h30m", "-1.5h", "2µs", "10s"],
+        &["", "1", "1d", "h", "1..5s", "-"],
+    );
+}
@@ -0,0 +1,54 @@
+// The tests call `(&Duplicates(items)).find()` like the derive, the borrow selects the implementation
+#![allow(clippy::needless_borrow)]
+
+use choreo_api::unique::{ComparedDuplicates as _, Duplicates, HashedDuplicates as _};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static COMPARISONS: AtomicUsize = AtomicUsize::new(0);
+
+/// An item counting how often it is compared
+#[derive(Eq)]
+struct Counted(u32);
+
+impl PartialEq for Counted {
+    fn eq(&self, other: &Self) -> bool {
+        COMPARISONS.fetch_add(1, Ordering::Relaxed);
+        self.0 == other.0
+    }
+}
+
+impl Hash for Counted {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+#[test]
+fn hashable_items_are_hashed() {
+    let mut items = (0..1000).map(Counted).collect::<Vec<_>>();
+    items.push(Counted(7));
+    assert_eq!((&Duplicates(&items)).find(), [(1000, 7)]);
+    // Comparing pairwise takes about half a million comparisons, hashing only compares on hash collisions
+    let comparisons = COMPARISONS.load(Ordering::Relaxed);
+    assert!(comparisons < 10_000, "{comparisons} comparisons");
+}
+
+#[test]
+fn other_items_are_compared() {
+    let items = [1.0, f64::NAN, 2.0, 1.0, f64::NAN, 1.0];
+    assert_eq!((&Duplicates(&items)).find(), [(3, 0), (5, 0)]);
+}
+
+#[test]
+fn keys_are_found_in_order() {
+    let keys = [
+        ("http", "TCP"),
+        ("http", "UDP"),
+        ("dns", "UDP"),
+        ("http", "TCP"),
+        ("dns", "UDP"),
+    ];
+    assert_eq!((&Duplicates(&keys)).find(), [(3, 0), (4, 2)]);
+    assert!((&Duplicates::<u8>(&[])).find().is_empty());
+}
@@ -1,3 +1,4 @@
+use ::choreo_api::{ExpressionRule, FieldPath, ValidationErrors};
 use ::choreo_meta::{TypeMeta, ObjectMeta, GroupVersionKind};
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq)]
@@ -145,4 +146,17 @@ impl Unstructured {
             self.data = serde_json::Value::Object(map);
         }
     }
-}
\ No newline at end of file
+}
+// validation
+
+impl Unstructured {
+    /// Evaluate expression rules against the object, `self` in a rule refers to the whole object,
+    /// e.g. `self.spec.minReplicas <= self.spec.maxReplicas`
+    pub fn validate_rules(&self, rules: &[ExpressionRule]) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        for rule in rules {
+            rule.validate_at(self, &FieldPath::root(), &mut errors);
+        }
+        errors.into_result()
+    }
+}
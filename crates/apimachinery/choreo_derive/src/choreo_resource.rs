@@ -164,16 +164,14 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
     }
     let visibility = derive_input.vis;
     let spec_ident = derive_input.ident;
+    // The root object takes the generics of the spec, e.g. `Root<P>` for `Spec<P>`
+    let generics = derive_input.generics;
 
     // Create a new root object
     let root_ident = Ident::new(&struct_name, Span::call_site());
     // Create a new status object
     let status_ident = Ident::new(&status_name, Span::call_site());
-    
-
-    //eprintln!("rootident_str {:?}", rootident_str);
 
-    
     let mut derive_paths: Vec<Path> = vec![
         syn::parse_quote! { #serde::Deserialize },
         syn::parse_quote! { Clone },
@@ -215,6 +213,7 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
         &visibility,
         &root_ident,
         &spec_ident,
+        &generics,
         &status_ident,
         &choreo_meta,
         &serde,
@@ -222,13 +221,12 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
         &choreo_meta_labels,
     );
 
-    //eprintln!("Generated code root_obj: \n{}", root_obj);
-
     let name = singular.unwrap_or_else(|| kind.to_ascii_lowercase());
     let plural: String = plural.unwrap_or_else(|| to_plural(&name));
     let impl_resource = generate_resource_trait_impl(
         &root_ident,
         &spec_ident,
+        &generics,
         &status_ident,
         &choreo_core,
         &choreo_meta,
@@ -238,8 +236,17 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
         plural.as_str(),
     );
 
+    let impl_defaultable = generate_defaultable_trait_impl(
+        &root_ident,
+        &spec_ident,
+        &generics,
+        &format!("{group}/{version}"),
+    );
+
     let impl_default = generate_default_trait_impl(
         &root_ident,
+        &spec_ident,
+        &generics,
         has_default,
         &choreo_meta,
     );
@@ -247,14 +254,17 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
     quote! {
         #root_obj
         #impl_resource
+        #impl_defaultable
         #impl_default
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_root_object(
     visibility: &syn::Visibility,
     root_ident: &Ident,
     spec_ident: &Ident,
+    generics: &syn::Generics,
     status_ident: &Ident,
     choreo_meta: &Path,
     serde: &Path,
@@ -263,6 +273,7 @@ fn generate_root_object(
 ) -> TokenStream {
     //let root_ident_str = root_ident.to_string();
     let quoted_serde = Literal::string(&serde.to_token_stream().to_string());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     quote! {
         #[automatically_derived]
@@ -272,20 +283,19 @@ fn generate_root_object(
             serde::Deserialize, 
             Clone, 
             Debug,
-            ChoreoDefault,
             ChoreoValidate,
         )]
         #[serde(rename_all = "camelCase")]
         #[serde(crate = #quoted_serde)]
-        #visibility struct #root_ident {
+        #visibility struct #root_ident #generics #where_clause {
             #visibility metadata: #choreo_meta::ObjectMeta,
-            #visibility spec: #spec_ident,
+            #visibility spec: #spec_ident #ty_generics,
             #[serde(skip_serializing_if = "Option::is_none")]
             #visibility status: Option<#status_ident>,
         }
 
-        impl #root_ident {
-            pub fn new(name: &str, spec: #spec_ident) -> Self {
+        impl #impl_generics #root_ident #ty_generics #where_clause {
+            pub fn new(name: &str, spec: #spec_ident #ty_generics) -> Self {
                 Self {
                     metadata: #choreo_meta::ObjectMeta {
                         annotations: #annotations,
@@ -315,9 +325,11 @@ fn generate_root_object(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_resource_trait_impl(
     root_ident: &Ident,
     spec_ident: &Ident,
+    generics: &syn::Generics,
     status_ident: &Ident,
     choreo_core: &Path,
     choreo_meta: &Path,
@@ -327,8 +339,9 @@ fn generate_resource_trait_impl(
     plural: &str,
 ) -> TokenStream {
     let api_ver = format!("{group}/{version}");
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     quote! {
-        impl #choreo_core::Resource for #root_ident {
+        impl #impl_generics #choreo_core::Resource for #root_ident #ty_generics #where_clause {
             type DynamicType = ();
 
             fn group(_: &()) -> std::borrow::Cow<'_, str> {
@@ -359,13 +372,13 @@ fn generate_resource_trait_impl(
                 &mut self.metadata
             }
 
-            type Spec = #spec_ident;
+            type Spec = #spec_ident #ty_generics;
 
-            fn spec(&self) -> &#spec_ident {
+            fn spec(&self) -> &#spec_ident #ty_generics {
                 &self.spec
             }
 
-            fn spec_mut(&mut self) -> &mut #spec_ident {
+            fn spec_mut(&mut self) -> &mut #spec_ident #ty_generics {
                 &mut self.spec
             }
 
@@ -383,14 +396,50 @@ fn generate_resource_trait_impl(
     }
 }
 
+/// Defaults of the root object are applied with a context holding its `apiVersion` and `metadata`,
+/// so the spec and status can take defaults from them, e.g. `#[cdefault(from = "metadata.name")]`.
+fn generate_defaultable_trait_impl(
+    root_ident: &Ident,
+    spec_ident: &Ident,
+    generics: &syn::Generics,
+    api_version: &str,
+) -> TokenStream {
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let mut generics = generics.clone();
+    let where_clause = generics.make_where_clause();
+    where_clause
+        .predicates
+        .push(parse_quote! { #spec_ident #ty_generics: ::choreo_api::Defaultable });
+    quote! {
+        impl #impl_generics ::choreo_api::Defaultable for #root_ident #ty_generics #where_clause {
+            fn apply_defaults_with(&mut self, context: &::choreo_api::DefaultContext) {
+                let context = context
+                    .clone()
+                    .with_api_version(#api_version)
+                    .with_metadata(&self.metadata);
+                ::choreo_api::Defaultable::apply_defaults_with(&mut self.spec, &context);
+                ::choreo_api::Defaultable::apply_defaults_with(&mut self.status, &context);
+            }
+        }
+    }
+}
+
 fn generate_default_trait_impl(
     rootident: &Ident,
+    spec_ident: &Ident,
+    generics: &syn::Generics,
     has_default: bool,
     choreo_meta: &Path,
 ) -> TokenStream {
     if has_default {
+        let (impl_generics, ty_generics, _) = generics.split_for_impl();
+        let mut generics = generics.clone();
+        let where_clause = generics.make_where_clause();
+        where_clause
+            .predicates
+            .push(parse_quote! { #spec_ident #ty_generics: Default });
         quote! {
-            impl Default for #rootident {
+            impl #impl_generics Default for #rootident #ty_generics #where_clause {
                 fn default() -> Self {
                     Self {
                         metadata: #choreo_meta::ObjectMeta::default(),
@@ -0,0 +1,66 @@
+use choreo_api::{Defaultable, FieldPath, Validate};
+use choreo_core::Resource;
+use choreo_derive::ChoreoResource;
+use default_derive::Default as ChoreoDefault;
+use serde::{Deserialize, Serialize};
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoDefault, ChoreoValidate, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct Port {
+    #[cdefault(80)]
+    #[cvalidate(ge = 1)]
+    number: Option<u32>,
+}
+
+#[derive(ChoreoDefault, ChoreoValidate, Deserialize, Serialize, Clone, Debug, Default)]
+pub struct ProxyStatus {
+    ready: Option<bool>,
+}
+
+/// A resource whose spec is generic over the kind of port it exposes
+#[derive(
+    ChoreoResource, ChoreoDefault, ChoreoValidate, Deserialize, Serialize, Clone, Debug, Default,
+)]
+#[choreo(
+    group = "example.com",
+    version = "v1",
+    kind = "Proxy",
+    status_name = "ProxyStatus",
+    derive = "Default"
+)]
+pub struct ProxySpec<P> {
+    #[cdefault(from = "metadata.name")]
+    backend: Option<String>,
+    ports: Vec<P>,
+}
+
+#[test]
+fn generic_spec_is_carried_to_the_resource() {
+    let spec = ProxySpec::<Port> {
+        backend: None,
+        ports: vec![Port::default()],
+    };
+    let mut proxy = Proxy::new("edge", spec);
+    assert_eq!(Proxy::<Port>::api_version(&()), "example.com/v1");
+    assert_eq!(proxy.meta().name.as_deref(), Some("edge"));
+
+    proxy.apply_defaults();
+    assert_eq!(proxy.spec().backend.as_deref(), Some("edge"));
+    assert_eq!(proxy.spec().ports[0].number, Some(80));
+    assert!(proxy.validate().is_ok());
+
+    proxy.spec_mut().ports.push(Port { number: Some(0) });
+    let errors = proxy.validate().unwrap_err();
+    assert_eq!(
+        errors.paths().collect::<Vec<_>>(),
+        [&FieldPath::root().field("spec").field("ports").index(1).field("number")]
+    );
+}
+
+#[test]
+fn generic_resource_round_trips_through_json() {
+    let proxy = Proxy::<Port>::default();
+    let json = serde_json::to_value(&proxy).unwrap();
+    let parsed: Proxy<Port> = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed.spec().ports.len(), 0);
+}
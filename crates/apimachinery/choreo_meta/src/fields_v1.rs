@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use choreo_api::{FieldPath, PathSegment};
+use serde_json::{Map, Value};
 
 /// FieldsV1 stores a set of fields in a data structure like a Trie, in JSON format.
 ///
@@ -9,3 +10,28 @@ use serde_json::Value;
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FieldsV1(Value);
+
+impl FieldsV1 {
+    /// Build the set of fields located by `paths`, e.g. the paths of a `choreo_api::DefaultingReport`
+    /// to record them as managed by a defaulter in `ObjectMeta.managed_fields`.
+    ///
+    /// Struct fields and map keys are written as `f:<name>`, list items as `i:<index>`.
+    pub fn from_paths<'a>(paths: impl IntoIterator<Item = &'a FieldPath>) -> Self {
+        let mut root = Map::new();
+        for path in paths {
+            let mut fields = &mut root;
+            for segment in path.segments() {
+                let key = match segment {
+                    PathSegment::Field(name) | PathSegment::Key(name) => format!("f:{name}"),
+                    PathSegment::Index(index) => format!("i:{index}"),
+                };
+                fields = fields
+                    .entry(key)
+                    .or_insert_with(|| Value::Object(Map::new()))
+                    .as_object_mut()
+                    .expect("fields are only inserted as objects");
+            }
+        }
+        Self(Value::Object(root))
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{managed_field_entry::ManagedFieldsEntry, relationship_reference::RelationReference};
 use crate::owner_reference::OwnerReference;
 use chrono::{DateTime, Utc};
+use choreo_api::{FieldPath, ValidationError, ValidationErrorKind, ValidationErrors};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -84,12 +85,28 @@ pub struct ObjectMeta {
     pub uid: Option<String>,
 }
 
-/* 
-impl ::default_derive::Defaultable for ObjectMeta {
-    fn apply_defaults(&self) {}
+/// The metadata has no defaults, it is populated by the system.
+impl ::choreo_api::Defaultable for ObjectMeta {
+    fn apply_defaults_with(&mut self, _context: &::choreo_api::DefaultContext) {}
 }
 
-impl ::validate_derive::Validate for ObjectMeta {
-    fn validate(&self) -> Result<(), String> {None}
-}
-*/
\ No newline at end of file
+impl ::choreo_api::Validate for ObjectMeta {
+    fn validate_at(&self, _path: &FieldPath, _errors: &mut ValidationErrors) {}
+
+    /// `name` and `namespace` cannot be updated. The name may still be set once,
+    /// e.g. when it is generated after the object was created.
+    fn validate_transition_at(&self, old: &Self, path: &FieldPath, errors: &mut ValidationErrors) {
+        if old.name.is_some() && self.name != old.name {
+            errors.push(
+                ValidationError::new(path.field("name"), ValidationErrorKind::Immutable)
+                    .with_constraint("once set"),
+            );
+        }
+        if self.namespace != old.namespace {
+            errors.push(ValidationError::new(
+                path.field("namespace"),
+                ValidationErrorKind::Immutable,
+            ));
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+use choreo_api::Defaultable;
+use choreo_meta::{Condition, ConditionStatus};
+
+#[test]
+fn defaults_leave_the_transition_time_unset() {
+    let mut status = ConditionStatus {
+        conditions: vec![Condition {
+            type_: "Ready".to_string(),
+            ..Default::default()
+        }],
+    };
+    let report = status.apply_defaults_report().unwrap();
+    assert!(report.is_empty(), "{report}");
+    assert_eq!(status.conditions[0].last_transition_time, None);
+}
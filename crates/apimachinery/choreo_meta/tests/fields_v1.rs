@@ -0,0 +1,31 @@
+use choreo_api::FieldPath;
+use choreo_meta::FieldsV1;
+use serde_json::json;
+
+#[test]
+fn fields_from_paths() {
+    let spec = FieldPath::root().field("spec");
+    let paths = [
+        spec.field("serviceName"),
+        spec.field("ports").index(1).field("protocol"),
+        spec.field("ports").index(1).field("name"),
+        spec.field("labels").key("tier"),
+    ];
+    let fields = FieldsV1::from_paths(&paths);
+    assert_eq!(
+        serde_json::to_value(&fields).unwrap(),
+        json!({
+            "f:spec": {
+                "f:serviceName": {},
+                "f:ports": {"i:1": {"f:protocol": {}, "f:name": {}}},
+                "f:labels": {"f:tier": {}},
+            }
+        })
+    );
+}
+
+#[test]
+fn no_paths() {
+    let fields = FieldsV1::from_paths(&[]);
+    assert_eq!(serde_json::to_value(&fields).unwrap(), json!({}));
+}
@@ -0,0 +1,32 @@
+use choreo_api::Validate;
+use choreo_meta::ObjectMeta;
+
+fn meta(name: Option<&str>, namespace: Option<&str>) -> ObjectMeta {
+    ObjectMeta {
+        name: name.map(str::to_string),
+        namespace: namespace.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn name_and_namespace_cannot_be_updated() {
+    let old = meta(Some("web"), Some("default"));
+    assert!(old.validate_update(&old).is_ok());
+
+    let errors = meta(Some("api"), Some("prod"))
+        .validate_update(&old)
+        .unwrap_err();
+    assert_eq!(
+        errors.to_string(),
+        "Field 'name' is immutable once set.\nField 'namespace' is immutable."
+    );
+}
+
+#[test]
+fn name_can_be_set_once() {
+    let old = meta(None, Some("default"));
+    assert!(meta(Some("web-x7k2"), Some("default"))
+        .validate_update(&old)
+        .is_ok());
+}
@@ -1,6 +1,11 @@
-use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{Attribute, DeriveInput, Field, Type, Data};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DataEnum, DeriveInput, Expr, ExprLit, ExprUnary, Field, Fields, Lit, LitStr, Token, Type, UnOp,
+};
 //use crate::enums::collect_all_enums;
 
 pub(crate) fn derive(input: TokenStream) -> TokenStream {
@@ -22,341 +27,652 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
     }
 
     let struct_name = &derive_input.ident;
+    // Fields of a type parameter apply their own defaults, so they have to implement `Defaultable`
+    let mut generics = derive_input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::choreo_api::Defaultable));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     let defaults = match &derive_input.data {
         syn::Data::Struct(data_struct) => data_struct
             .fields
             .iter()
-            .map(|field| generate_set_default_for_field(field))
+            .enumerate()
+            .map(|(index, field)| {
+                let target = match &field.ident {
+                    Some(ident) => FieldTarget::named(ident),
+                    None => FieldTarget::unnamed(index),
+                };
+                generate_set_default_for_field(field, &target)
+            })
             .collect::<Vec<_>>(),
+        syn::Data::Enum(data_enum) => vec![generate_set_default_for_enum(data_enum)],
         _ => vec![],
     };
 
+    // An enum with a variant marked `#[cdefault]` also gets a `Default` implementation
+    let default_variant = match &derive_input.data {
+        syn::Data::Enum(data_enum) => match generate_default_variant(&derive_input, data_enum) {
+            Ok(default_variant) => default_variant,
+            Err(err) => return err.to_compile_error(),
+        },
+        _ => quote! {},
+    };
+
     // this is the expanded code the compiler adds when the ChoreoDefault derive is added to a struct
     let expanded = quote! {
-        impl ::choreo_api::Defaultable for #struct_name {
-            /// Set defaults for all fields where applicable
-            fn apply_defaults(&mut self) {
+        impl #impl_generics ::choreo_api::Defaultable for #struct_name #ty_generics #where_clause {
+            /// Set defaults for all fields where applicable, `context` is passed down to nested values
+            #[allow(unused_variables)]
+            fn apply_defaults_with(&mut self, context: &::choreo_api::DefaultContext) {
                 #(#defaults)*
             }
         }
+
+        #default_variant
     };
 
-    TokenStream::from(expanded)
+    expanded
 }
 
-fn generate_set_default_for_field(field: &Field) -> TokenStream {
-    let field_name = field.ident.as_ref().expect("Expected named field");
-    let field_type = &field.ty;
-    let attr = extract_default_attribute(field);
+/// Generate `Default` for an enum with a variant marked `#[cdefault]`. The fields of that variant
+/// take their own `Default` and then their `#[cdefault(...)]` defaults, so `Default::default()` equals
+/// the defaulted value, e.g. `#[cdefault] Pending { #[cdefault("Scheduling")] reason: Option<String> }`
+/// defaults to the reason `Scheduling`.
+///
+/// The enum also implements `choreo_api::IsSet`, every variant but the default one is set, so a field
+/// of the enum can take a default with `when = "zero"`.
+fn generate_default_variant(derive_input: &DeriveInput, data_enum: &DataEnum) -> syn::Result<TokenStream> {
+    let mut default_variants = data_enum.variants.iter().filter_map(|variant| {
+        extract_default_attribute_from(&variant.attrs).map(|attr| (variant, attr))
+    });
+    let Some((variant, attr)) = default_variants.next() else {
+        return Ok(quote! {});
+    };
+    let enum_name = &derive_input.ident;
+    if let Some((_, duplicate)) = default_variants.next() {
+        return Err(syn::Error::new_spanned(
+            duplicate,
+            format!("Only one variant of `{enum_name}` can be marked `#[cdefault]`"),
+        ));
+    }
+    if !matches!(attr.meta, syn::Meta::Path(_)) {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "`#[cdefault]` marks the default variant and takes no value",
+        ));
+    }
 
-    if is_type(field_type, "Option") {
-        if let Some(inner_type) = extract_inner_type_for_type(field_type, "Option") {
-            return generate_default_for_option(field_name, &inner_type, attr);
-        }
-    } else if is_type(field_type, "Vec") {
-        if let Some(inner_type) = extract_inner_type_for_type(field_type, "Vec") {
-            return generate_default_for_container(field_name, &inner_type, "Vec");
+    let variant_ident = &variant.ident;
+    let pattern = match &variant.fields {
+        Fields::Named(_) => quote! { Self::#variant_ident { .. } },
+        Fields::Unnamed(_) => quote! { Self::#variant_ident(..) },
+        Fields::Unit => quote! { Self::#variant_ident },
+    };
+    let value = match &variant.fields {
+        Fields::Named(fields) => {
+            let field_idents = fields.named.iter().map(|field| &field.ident);
+            quote! { Self::#variant_ident { #(#field_idents: ::core::default::Default::default()),* } }
         }
-    } else if is_type(field_type, "HashMap") {
-        if let Some((_key, inner_type)) = extract_key_value_types_for_map(field_type, "HashMap") {
-            return generate_default_for_container(field_name, &inner_type, "HashMap");
+        Fields::Unnamed(fields) => {
+            let defaults = fields
+                .unnamed
+                .iter()
+                .map(|_| quote! { ::core::default::Default::default() });
+            quote! { Self::#variant_ident(#(#defaults),*) }
         }
-    } else if is_type(field_type, "BTreeMap") {
-        if let Some((_key, inner_type)) = extract_key_value_types_for_map(field_type, "BTreeMap") {
-            return generate_default_for_container(field_name, &inner_type, "BTreeMap");
+        Fields::Unit => quote! { Self::#variant_ident },
+    };
+
+    let (impl_generics, ty_generics, _) = derive_input.generics.split_for_impl();
+    let mut generics = derive_input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for field in &variant.fields {
+        let field_type = &field.ty;
+        where_clause
+            .predicates
+            .push(syn::parse_quote_spanned! { field_type.span()=> #field_type: ::core::default::Default });
+    }
+    where_clause.predicates.push(syn::parse_quote! { Self: ::choreo_api::Defaultable });
+    let is_set_where_clause = &derive_input.generics.where_clause;
+    Ok(quote! {
+        impl #impl_generics ::core::default::Default for #enum_name #ty_generics #where_clause {
+            fn default() -> Self {
+                let mut value = #value;
+                ::choreo_api::Defaultable::apply_defaults(&mut value);
+                value
+            }
         }
-    } else if is_nested_struct(field_type) {
-        if field_name == "metadata" {
-           return  quote! {} // Skip the `metadata` field
-        } else {
-            return quote! {
-                self.#field_name.apply_defaults();
+
+        impl #impl_generics ::choreo_api::IsSet for #enum_name #ty_generics #is_set_where_clause {
+            fn is_set(&self) -> bool {
+                !matches!(self, #pattern)
             }
         }
-    }
-    quote! {} // No action for unsupported types
+    })
 }
 
-fn generate_default_for_container(
-    field_name: &proc_macro2::Ident,
-    inner_type: &Type,
-    container: &str,
-) -> TokenStream {
-    if is_nested_struct(inner_type) {
-        if !is_owned_type(inner_type) {
-            return quote! {
-                compile_error!(concat!(container, " struct type is not owned"));
-            };
+/// FieldTarget is the field defaults are applied to.
+struct FieldTarget {
+    /// Name of the field, used in error messages
+    name: String,
+    /// Expression evaluating to the field value, e.g. `self.name`
+    access: TokenStream,
+}
+
+impl FieldTarget {
+    /// Target the named field `ident` of `self`
+    fn named(ident: &proc_macro2::Ident) -> Self {
+        Self {
+            name: ident.to_string(),
+            access: quote! { self.#ident },
+        }
+    }
+
+    /// Target the unnamed field at `index` of a tuple struct
+    fn unnamed(index: usize) -> Self {
+        let member = syn::Index::from(index);
+        Self {
+            name: index.to_string(),
+            access: quote! { self.#member },
+        }
+    }
+
+    /// Target a field that is bound by reference in a match pattern, e.g. the field of an enum variant
+    fn bound(name: String, binding: &proc_macro2::Ident) -> Self {
+        Self {
+            name,
+            access: quote! { (*#binding) },
         }
-        return match container {
-            "Vec" => quote! {
-                for item in &mut self.#field_name {
-                    item.apply_defaults();
-                }
-            },
-            "HashMap" | "BTreeMap" => quote! {
-                for (_key, value) in &mut self.#field_name {
-                    value.apply_defaults();
-                }
-            },
-            _ => quote! {},
-        };
     }
-    quote! {}
 }
 
-fn generate_default_for_option_container(
-    field_name: &proc_macro2::Ident,
-    inner_type: &Type,
-    container: &str,
-) -> TokenStream {
-    if is_nested_struct(inner_type) {
-        if !is_owned_type(inner_type) {
-            return quote! {
-                compile_error!(concat!("Optional ", container, " type is not owned"));
-            };
+/// Generate a match on the active variant that applies the defaults of that variant's fields.
+fn generate_set_default_for_enum(data_enum: &DataEnum) -> TokenStream {
+    let arms = data_enum.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+
+        let mut bindings = Vec::new();
+        let mut defaults = Vec::new();
+        for (index, field) in variant.fields.iter().enumerate() {
+            let binding = format_ident!("__field{}", index);
+            let name = field
+                .ident
+                .as_ref()
+                .map_or_else(|| index.to_string(), |ident| ident.to_string());
+            let field_defaults =
+                generate_set_default_for_field(field, &FieldTarget::bound(name, &binding));
+            if field_defaults.is_empty() {
+                bindings.push(quote! { _ });
+            } else {
+                bindings.push(quote! { #binding });
+                defaults.push(field_defaults);
+            }
         }
-        return match container {
-            "Vec" => quote! {
-                if let Some(inner_vec) = self.#field_name.as_mut() {
-                    for item in inner_vec {
-                        item.apply_defaults();
-                    }
-                }
-            },
-            "HashMap" | "BTreeMap" => quote! {
-                if let Some(inner_map) = self.#field_name.as_mut() {
-                    for (_key, value) in inner_map {
-                        value.apply_defaults();
-                    }
-                }
-            },
-            _ => quote! {},
+
+        let pattern = match &variant.fields {
+            Fields::Named(fields) => {
+                let field_idents = fields.named.iter().map(|field| &field.ident);
+                quote! { Self::#variant_ident { #(#field_idents: #bindings),* } }
+            }
+            Fields::Unnamed(_) => quote! { Self::#variant_ident(#(#bindings),*) },
+            Fields::Unit => quote! { Self::#variant_ident },
         };
+
+        quote! {
+            #pattern => {
+                #(#defaults)*
+            }
+        }
+    });
+
+    quote! {
+        match self {
+            #(#arms)*
+        }
+    }
+}
+
+fn generate_set_default_for_field(field: &Field, target: &FieldTarget) -> TokenStream {
+    let field_type = &field.ty;
+    let attr = extract_default_attribute(field);
+
+    // `#[cdefault(skip)]` leaves the field as it is, e.g. for types that do not implement `Defaultable`
+    if attr.is_some_and(is_skip) {
+        return quote! {};
+    }
+    let attribute_default = match attr {
+        Some(attr) => generate_default_with_attribute(attr, target, field_type),
+        None => quote! {},
+    };
+    // The field then applies its own defaults, also to a value the attribute just set. `choreo_api`
+    // implements `Defaultable` for `Option`, `Vec`, maps, `Box`, primitives and `chrono::DateTime`.
+    // A missing implementation is reported at the field type.
+    let access = &target.access;
+    let apply_defaults_with = quote_spanned! { field_type.span()=>
+        <#field_type as ::choreo_api::Defaultable>::apply_defaults_with
+    };
+    quote! {
+        #attribute_default
+        #apply_defaults_with(&mut #access, context);
     }
-    quote! {}
 }
 
 /// Extract the default attribute from the `#[default(...)]` if present.
 ///
 /// Returns the default `Attribute` from the attributes, or `None` if the attribute is not present.
 fn extract_default_attribute(field: &syn::Field) -> Option<&Attribute> {
-    for attr in &field.attrs {
-        // Check if the attribute is `default`
-        if attr.path().is_ident("cdefault") {
-            // Try to parse the attribute's argument as a general expression
-            return Some(attr);
-        }
-    }
-    None
+    extract_default_attribute_from(&field.attrs)
 }
 
-fn generate_default_for_option(
-    field_name: &proc_macro2::Ident,
-    inner_type: &Type,
-    attr: Option<&Attribute>,
-) -> TokenStream {
-    match attr {
-        Some(attr) => {
-            // default attribute is present
-            let generated_code =
-                generate_default_for_option_with_attribute(attr, field_name, inner_type);
-            return generated_code;
-        }
-        None => {
-            // no default attribute present
-            if is_type(inner_type, "Vec") {
-                if let Some(inner_type) = extract_inner_type_for_type(inner_type, "Vec") {
-                    return generate_default_for_option_container(field_name, &inner_type, "Vec");
-                }
-            } else if is_type(inner_type, "HashMap") {
-                if let Some((_key, inner_type)) =
-                    extract_key_value_types_for_map(inner_type, "HashMap")
-                {
-                    return generate_default_for_option_container(
-                        field_name,
-                        &inner_type,
-                        "HashMap",
-                    );
-                }
-            } else if is_type(inner_type, "BTreeMap") {
-                if let Some((_key, inner_type)) =
-                    extract_key_value_types_for_map(inner_type, "BTreeMap")
-                {
-                    return generate_default_for_option_container(
-                        field_name,
-                        &inner_type,
-                        "BTreeMap",
-                    );
+fn extract_default_attribute_from(attrs: &[Attribute]) -> Option<&Attribute> {
+    // Check if the attribute is `cdefault`
+    attrs.iter().find(|attr| attr.path().is_ident("cdefault"))
+}
+
+/// Returns true for `#[cdefault(skip)]`
+fn is_skip(attr: &Attribute) -> bool {
+    attr.parse_args::<syn::Ident>().is_ok_and(|ident| ident == "skip")
+}
+
+/// DefaultAttr is the content of `#[cdefault(...)]`: the default value, e.g. `8080`, `"nginx"`,
+/// `["a", "b"]`, `{"tier": "web"}`, `fn = default_image`, `const = DEFAULT_PORT`,
+/// `expr = "Duration::from_secs(30)"` or `from = "metadata.name"`, optionally followed by
+/// `when = "zero"` or `when = "none"`.
+struct DefaultAttr {
+    value: DefaultValue,
+    when: Option<LitStr>,
+}
+
+enum DefaultValue {
+    /// A literal or a list literal
+    Literal(Expr),
+    /// A map literal, e.g. `{"tier": "web"}`
+    Map(Vec<(Expr, Expr)>),
+    /// `fn = default_image`: the value returned by the function, called without arguments
+    Function(syn::Path),
+    /// `const = DEFAULT_PORT`: the value of a constant or static
+    Constant(syn::Path),
+    /// `expr = "Duration::from_secs(30)"`: the value of an expression, evaluated on every call
+    Expression(Expr),
+    /// `from = "metadata.name"`: the value at a path of the `choreo_api::DefaultContext`
+    Context(LitStr),
+}
+
+/// The top level names of the `choreo_api::DefaultContext` paths
+const CONTEXT_ROOTS: [&str; 3] = ["apiVersion", "metadata", "values"];
+
+impl Parse for DefaultAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let value = if input.peek(Token![fn]) && input.peek2(Token![=]) {
+            input.parse::<Token![fn]>()?;
+            input.parse::<Token![=]>()?;
+            DefaultValue::Function(input.parse()?)
+        } else if input.peek(Token![const]) && input.peek2(Token![=]) {
+            input.parse::<Token![const]>()?;
+            input.parse::<Token![=]>()?;
+            DefaultValue::Constant(input.parse()?)
+        } else if input.peek(syn::Ident) && input.peek2(Token![=]) {
+            let name = input.parse::<Ident>()?;
+            input.parse::<Token![=]>()?;
+            if name == "expr" {
+                // The expression is parsed with the span of the string, so errors in it are located there.
+                // Strings that are not even valid tokens are reported at the string itself
+                let expr = input.parse::<LitStr>()?;
+                DefaultValue::Expression(expr.parse().map_err(|err| {
+                    syn::Error::new(expr.span(), format!("Invalid `expr = \"{}\"`: {err}", expr.value()))
+                })?)
+            } else if name == "from" {
+                let path = input.parse::<LitStr>()?;
+                let root = path.value().split('.').next().unwrap_or_default().to_string();
+                if !CONTEXT_ROOTS.contains(&root.as_str()) {
+                    return Err(syn::Error::new(
+                        path.span(),
+                        format!(
+                            "Unknown context `{root}`, expected a path below {}",
+                            CONTEXT_ROOTS.join(", ")
+                        ),
+                    ));
                 }
-            } else if is_nested_struct(inner_type) {
-                return quote! {
-                    if let Some(inner_item) = &mut self.#field_name {
-                        inner_item.apply_defaults();
-                    }
-                };
+                DefaultValue::Context(path)
+            } else {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("expected a value, `fn`, `const`, `expr` or `from`, found `{name}`"),
+                ));
+            }
+        } else if input.peek(syn::token::Brace) {
+            let content;
+            syn::braced!(content in input);
+            let entries = content.parse_terminated(
+                |entry: ParseStream| {
+                    let key = entry.parse::<Expr>()?;
+                    entry.parse::<Token![:]>()?;
+                    Ok((key, entry.parse::<Expr>()?))
+                },
+                Token![,],
+            )?;
+            DefaultValue::Map(entries.into_iter().collect())
+        } else {
+            DefaultValue::Literal(input.parse()?)
+        };
+        let mut when = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let name = input.call(Ident::parse_any)?;
+            if name != "when" {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("expected `when = \"zero\"`, found `{name}`"),
+                ));
             }
+            input.parse::<Token![=]>()?;
+            when = Some(input.parse()?);
         }
+        Ok(Self { value, when })
     }
-    quote! {} // No action if we cannot extract the inner type
 }
 
-fn generate_default_for_option_with_attribute(
+impl DefaultAttr {
+    /// Returns true for `#[cdefault("none")]`, an optional field without a default
+    fn is_none(&self) -> bool {
+        matches!(
+            &self.value,
+            DefaultValue::Literal(Expr::Lit(ExprLit { lit: Lit::Str(lit), .. })) if lit.value() == "none"
+        )
+    }
+}
+
+/// When decides which values of a field are replaced by the default.
+#[derive(PartialEq)]
+enum When {
+    /// `when = "none"`: an optional field that is `None`, the policy of `Option` fields
+    None,
+    /// `when = "zero"`: a field that is not set according to `choreo_api::IsSet`, e.g. `0`, `""`
+    /// or an empty `Vec`. An optional field is replaced when it is `None` or holds such a value.
+    ///
+    /// This is the policy of `Vec` and map fields. Other fields, e.g. a `bool` or `u32`, have to
+    /// state it: a zero can not be told apart from an explicit `false` or `0`, which would be replaced
+    /// as well. An `Option` field only replaces a missing value.
+    Zero,
+}
+
+fn generate_default_with_attribute(
     attr: &syn::Attribute,
-    field_name: &proc_macro2::Ident,
-    inner_type: &Type,
+    field: &FieldTarget,
+    field_type: &Type,
 ) -> TokenStream {
-    match get_type_string(&inner_type).as_deref() {
-        Some(type_name) if is_integer(type_name) => {
-            if let Ok(lit_int) = attr.parse_args::<syn::LitInt>() {
-                let value = lit_int.to_token_stream();
-                return quote! {
-                    if self.#field_name.is_none() {
-                        self.#field_name = Some(#value);
-                    }
-                };
-            }
+    let default = match attr.parse_args::<DefaultAttr>() {
+        Ok(default) => default,
+        Err(err) => return err.to_compile_error(),
+    };
+    let option_type = extract_inner_type_for_type(field_type, "Option");
+    let when = match default.when.as_ref().map(LitStr::value).as_deref() {
+        None if option_type.is_some() => When::None,
+        None | Some("zero") => When::Zero,
+        Some("none") if option_type.is_some() => When::None,
+        Some("none") => {
+            return syn::Error::new_spanned(
+                &default.when,
+                format!(
+                    "`when = \"none\"` requires an `Option` field, use `when = \"zero\"` for field `{}`",
+                    field.name
+                ),
+            )
+            .to_compile_error()
         }
-        Some(type_name) if is_float(type_name) => {
-            if let Ok(lit_float) = attr.parse_args::<syn::LitFloat>() {
-                let value = lit_float.to_token_stream();
-                return quote! {
-                    if self.#field_name.is_none() {
-                        self.#field_name = Some(#value);
-                    }
-                };
-            } else if let Ok(lit_int) = attr.parse_args::<syn::LitInt>() {
-                let value = lit_int.base10_parse::<f64>().unwrap();
-                return quote! {
-                    if self.#field_name.is_none() {
-                        self.#field_name = Some(#value);
-                    }
-                };
-            }
+        Some(when) => {
+            return syn::Error::new_spanned(
+                &default.when,
+                format!("Unknown `when = \"{when}\"`, expected `\"none\"` or `\"zero\"`"),
+            )
+            .to_compile_error()
         }
-        Some(type_name) if is_string(type_name) => {
-            if let Ok(lit_str) = attr.parse_args::<syn::LitStr>() {
-                let value = lit_str.value();
-                return quote! {
-                    if self.#field_name.is_none() {
-                        self.#field_name = Some(#value.to_string());
-                    }
-                };
+    };
+
+    if option_type.is_some() && default.is_none() {
+        return quote! {};
+    }
+    let is_collection = ["Vec", "HashMap", "BTreeMap"]
+        .iter()
+        .any(|collection| is_type(field_type, collection));
+    if option_type.is_none() && default.when.is_none() && !is_collection {
+        let typ = quote!(#field_type).to_string().replace(' ', "");
+        return syn::Error::new_spanned(
+            attr,
+            format!(
+                "A default for field `{}` of type `{typ}` would also replace an explicit {}. \
+                 Use `Option<{typ}>` to only default a missing value, or add `when = \"zero\"` to replace it",
+                field.name,
+                zero_value(field_type)
+            ),
+        )
+        .to_compile_error();
+    }
+    let access = &field.access;
+    let condition = match (&option_type, when) {
+        (None, _) => {
+            let is_set =
+                quote_spanned! { field_type.span()=> <#field_type as ::choreo_api::IsSet>::is_set };
+            quote! { !#is_set(&#access) }
+        }
+        (Some(_), When::None) => quote! { #access.is_none() },
+        (Some(_), When::Zero) => quote! { !#access.as_ref().is_some_and(::choreo_api::IsSet::is_set) },
+    };
+    let set = |value: TokenStream| match option_type {
+        Some(_) => quote! { #access = Some(#value); },
+        None => quote! { #access = #value; },
+    };
+    let assignment = match &default.value {
+        // A value missing from the context leaves the field as it is
+        DefaultValue::Context(path) => {
+            let set = set(quote! { value });
+            quote! {
+                if let Some(value) = context.resolve(#path) {
+                    #set
+                }
             }
         }
-        Some(type_name) if is_boolean(type_name) => {
-            if let Ok(lit_bool) = attr.parse_args::<syn::LitBool>() {
-                let value = lit_bool.value();
-                return quote! {
-                    if self.#field_name.is_none() {
-                        self.#field_name = Some(#value);
-                    }
-                };
+        value => {
+            let value_type = option_type.as_ref().unwrap_or(field_type);
+            match generate_value(value, value_type, attr, field, field_type) {
+                Ok(value) => set(value),
+                Err(err) => return err.to_compile_error(),
             }
         }
-        Some(_) => {
-            if let Ok(lit_str) = attr.parse_args::<syn::LitStr>() {
-                let value = lit_str.value();
-                if value.starts_with("enum=") {
-                    let variant = value.trim_start_matches("enum=");
-                    let variant_ident = syn::Ident::new(variant, proc_macro2::Span::call_site());
-                    return quote! {
-                        if self.#field_name.is_none() {
-                            self.#field_name = Some(#inner_type::#variant_ident);
-                        }
-                    };
-                }
-                if value == "none" {
-                    return quote! {};
-                }
+    };
+    quote! {
+        if #condition {
+            #assignment
+        }
+    }
+}
+
+/// Generate the expression of type `typ` for the default `value`. Lists and maps are generated
+/// element by element, so `["a", "b"]` is a valid default for a `Vec<String>`.
+///
+/// An invalid value is reported at the value, `field_type` is the type of the whole field.
+fn generate_value(
+    value: &DefaultValue,
+    typ: &Type,
+    attr: &Attribute,
+    field: &FieldTarget,
+    field_type: &Type,
+) -> syn::Result<TokenStream> {
+    let invalid = |spanned: &dyn ToTokens| {
+        syn::Error::new_spanned(
+            spanned,
+            format!(
+                "Invalid `#[cdefault(...)]` for field `{}` of type `{}`, expected {}",
+                field.name,
+                quote!(#field_type).to_string().replace(' ', ""),
+                expected_value(typ)
+            ),
+        )
+    };
+    let expr = match value {
+        DefaultValue::Map(entries) => {
+            let key_value_types = (is_type(typ, "HashMap") || is_type(typ, "BTreeMap"))
+                .then(|| extract_key_value_types_for_map(typ))
+                .flatten();
+            let Some((key_type, value_type)) = key_value_types else {
+                return Err(invalid(attr));
+            };
+            let mut keys = Vec::new();
+            let mut values = Vec::new();
+            for (key, value) in entries {
+                let key = DefaultValue::Literal(key.clone());
+                keys.push(generate_value(&key, &key_type, attr, field, field_type)?);
+                let value = DefaultValue::Literal(value.clone());
+                values.push(generate_value(&value, &value_type, attr, field, field_type)?);
             }
+            return Ok(quote! { [#((#keys, #values)),*].into_iter().collect() });
+        }
+        DefaultValue::Literal(expr) => expr,
+        // Computed values are not checked here, the compiler checks their type. They are converted
+        // with `Into`, so e.g. a `const DEFAULT_IMAGE: &str` is a valid default of a `String` field.
+        DefaultValue::Function(path) => {
+            return Ok(quote_spanned! { path.span()=> ::core::convert::Into::into(#path()) })
         }
-        None => {}
+        DefaultValue::Constant(path) => {
+            return Ok(quote_spanned! { path.span()=> ::core::convert::Into::into(#path) })
+        }
+        DefaultValue::Expression(expr) => {
+            return Ok(quote_spanned! { expr.span()=> ::core::convert::Into::into(#expr) })
+        }
+        // Context values are only taken for the field itself, see `generate_default_with_attribute`
+        DefaultValue::Context(path) => return Err(invalid(path)),
+    };
+
+    if let Some(inner_type) = extract_inner_type_for_type(typ, "Vec") {
+        let Expr::Array(array) = expr else {
+            return Err(invalid(expr));
+        };
+        let items = array
+            .elems
+            .iter()
+            .map(|item| {
+                let item = DefaultValue::Literal(item.clone());
+                generate_value(&item, &inner_type, attr, field, field_type)
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        return Ok(quote! { vec![#(#items),*] });
     }
 
-    // Unified fallback for unsupported types or errors
-    quote! {
-        compile_error!(concat!(
-            "Unsupported or invalid `#[cdefault(...)]` attribute format for field `",
-            stringify!(#field_name),
-            "`."
-        ));
+    // A variant or a constructor, e.g. `Status::Active` or `Status::Pending { reason: "init".into() }`,
+    // is used as written, so the compiler reports an unknown variant at the attribute
+    let type_name = get_type_string(typ);
+    let is_constructor = matches!(expr, Expr::Path(_) | Expr::Call(_) | Expr::Struct(_));
+    if is_constructor && !type_name.as_deref().is_some_and(is_primitive) {
+        return Ok(expr.to_token_stream());
     }
-}
 
-fn is_nested_struct(typ: &syn::Type) -> bool {
-    if let Some(type_name) = get_type_name(typ) {
-        return !matches!(
-            type_name.as_str(),
-            "i8" | "i16"
-                | "i32"
-                | "i64"
-                | "u8"
-                | "u16"
-                | "u32"
-                | "u64"
-                | "f32"
-                | "f64"
-                | "bool"
-                | "String"
-                | "Option"
-                | "Vec"
-                | "HashMap"
-                | "BTreeMap"
-        );
+    // A negative number, e.g. `-1`, is the negated literal of a signed or float type
+    if let Expr::Unary(ExprUnary { op: UnOp::Neg(_), expr: operand, .. }) = expr {
+        let is_signed = type_name.as_deref().is_some_and(|type_name| {
+            (is_integer(type_name) && type_name.starts_with('i')) || is_float(type_name)
+        });
+        return match &**operand {
+            Expr::Lit(ExprLit { lit: Lit::Int(_) | Lit::Float(_), .. }) if is_signed => {
+                let operand = DefaultValue::Literal((**operand).clone());
+                let value = generate_value(&operand, typ, attr, field, field_type)?;
+                Ok(quote! { -#value })
+            }
+            _ => Err(invalid(expr)),
+        };
     }
-    false
-}
 
-fn is_owned_type(typ: &syn::Type) -> bool {
-    // Check if the type represents an owned struct
-    if let syn::Type::Path(type_path) = typ {
-        type_path
-            .path
-            .segments
-            .iter()
-            .all(|segment| segment.ident != "Ref" && segment.ident != "Borrowed")
-    } else {
-        false
+    let Expr::Lit(ExprLit { lit, .. }) = expr else {
+        return Err(invalid(expr));
+    };
+    match (type_name.as_deref(), lit) {
+        (Some(type_name), Lit::Int(lit_int)) if is_integer(type_name) => Ok(lit_int.to_token_stream()),
+        (Some(type_name), Lit::Float(lit_float)) if is_float(type_name) => Ok(lit_float.to_token_stream()),
+        (Some(type_name), Lit::Int(lit_int)) if is_float(type_name) => {
+            let value = Literal::f64_unsuffixed(lit_int.base10_parse::<f64>()?);
+            Ok(quote! { #value })
+        }
+        (Some(type_name), Lit::Str(lit_str)) if is_string(type_name) => {
+            let value = lit_str.value();
+            Ok(quote! { #value.to_string() })
+        }
+        (Some(type_name), Lit::Bool(lit_bool)) if is_boolean(type_name) => Ok(lit_bool.to_token_stream()),
+        // The string form `"enum=Active"` of a unit variant is still accepted
+        (Some(type_name), Lit::Str(lit_str))
+            if !is_primitive(type_name) && lit_str.value().starts_with("enum=") =>
+        {
+            let value = lit_str.value();
+            let variant = value.trim_start_matches("enum=");
+            // The variant is located at the attribute, so an unknown variant is reported there
+            let mut variant_ident = syn::parse_str::<syn::Ident>(variant).map_err(|_| {
+                syn::Error::new(
+                    lit_str.span(),
+                    format!(
+                        "`enum=` expects the name of a variant of `{}`, found `{variant}`",
+                        quote!(#typ).to_string().replace(' ', "")
+                    ),
+                )
+            })?;
+            variant_ident.set_span(lit_str.span());
+            Ok(quote! { #typ::#variant_ident })
+        }
+        _ => Err(invalid(lit)),
     }
 }
 
-fn get_type_name(field_type: &Type) -> Option<String> {
-    if let Type::Path(type_path) = field_type {
-        if let Some(segment) = type_path.path.segments.last() {
-            return Some(segment.ident.to_string());
+/// Describe the default value expected for a value of type `typ`
+fn expected_value(typ: &Type) -> &'static str {
+    if is_type(typ, "Vec") {
+        return r#"a list literal, e.g. `#[cdefault(["a", "b"])]`"#;
+    }
+    if is_type(typ, "HashMap") || is_type(typ, "BTreeMap") {
+        return r#"a map literal, e.g. `#[cdefault({"tier": "web"})]`"#;
+    }
+    match get_type_string(typ).as_deref() {
+        Some(type_name) if is_integer(type_name) => "an integer literal, e.g. `#[cdefault(8080)]`",
+        Some(type_name) if is_float(type_name) => "a float or integer literal, e.g. `#[cdefault(0.5)]`",
+        Some(type_name) if is_string(type_name) => r#"a string literal, e.g. `#[cdefault("nginx")]`"#,
+        Some(type_name) if is_boolean(type_name) => "`true` or `false`, e.g. `#[cdefault(true)]`",
+        Some(_) => {
+            r#"a variant, e.g. `#[cdefault(Status::Active)]`, `fn = ...`, `const = ...`, `expr = "..."` or `"none"`"#
         }
+        None => r#"`fn = ...`, `const = ...`, `expr = "..."` or `#[cdefault("none")]`"#,
+    }
+}
+
+/// Describe the value of type `typ` that is not set according to `choreo_api::IsSet`
+fn zero_value(typ: &Type) -> &'static str {
+    match get_type_string(typ).as_deref() {
+        Some(type_name) if is_integer(type_name) || is_float(type_name) => "`0`",
+        Some(type_name) if is_string(type_name) => r#"`""`"#,
+        Some(type_name) if is_boolean(type_name) => "`false`",
+        Some("char") => r"`'\0'`",
+        Some("Duration") => "`Duration::ZERO`",
+        _ => "value that is not set according to `choreo_api::IsSet`",
     }
-    None
 }
 
 fn is_integer(typ: &str) -> bool {
-    return matches!(
+    matches!(
         typ,
-        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64"
-    );
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+    )
 }
 
 fn is_float(typ: &str) -> bool {
-    return matches!(typ, "f32" | "f64");
+    matches!(typ, "f32" | "f64")
 }
 
 fn is_string(typ: &str) -> bool {
-    return typ == "String";
+    typ == "String"
 }
 
 fn is_boolean(typ: &str) -> bool {
-    return typ == "bool";
+    typ == "bool"
 }
 
-fn is_type(field_type: &Type, typ: &str) -> bool {
-    if let Type::Path(type_path) = field_type {
-        if let Some(segment) = type_path.path.segments.first() {
-            return segment.ident == typ;
-        }
-    }
-    false
+fn is_primitive(typ: &str) -> bool {
+    is_integer(typ) || is_float(typ) || is_string(typ) || is_boolean(typ)
 }
 
 fn get_type_string(typ: &syn::Type) -> Option<String> {
@@ -372,7 +688,7 @@ fn get_type_string(typ: &syn::Type) -> Option<String> {
 
 fn extract_inner_type_for_type(field_type: &Type, ident: &str) -> Option<Type> {
     if let Type::Path(type_path) = field_type {
-        if let Some(segment) = type_path.path.segments.first() {
+        if let Some(segment) = type_path.path.segments.last() {
             if segment.ident == ident {
                 if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
                     if let Some(syn::GenericArgument::Type(inner_type)) = args.args.first() {
@@ -385,21 +701,29 @@ fn extract_inner_type_for_type(field_type: &Type, ident: &str) -> Option<Type> {
     None
 }
 
-fn extract_key_value_types_for_map(field_type: &Type, ident: &str) -> Option<(Type, Type)> {
+
+fn is_type(field_type: &Type, typ: &str) -> bool {
+    if let Type::Path(type_path) = field_type {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == typ;
+        }
+    }
+    false
+}
+
+fn extract_key_value_types_for_map(field_type: &Type) -> Option<(Type, Type)> {
     if let Type::Path(type_path) = field_type {
-        if let Some(segment) = type_path.path.segments.first() {
-            if segment.ident == ident {
-                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
-                    let mut types = args.args.iter().filter_map(|arg| {
-                        if let syn::GenericArgument::Type(typ) = arg {
-                            Some(typ.clone())
-                        } else {
-                            None
-                        }
-                    });
-                    if let (Some(key_type), Some(value_type)) = (types.next(), types.next()) {
-                        return Some((key_type, value_type));
+        if let Some(segment) = type_path.path.segments.last() {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                let mut types = args.args.iter().filter_map(|arg| {
+                    if let syn::GenericArgument::Type(typ) = arg {
+                        Some(typ.clone())
+                    } else {
+                        None
                     }
+                });
+                if let (Some(key_type), Some(value_type)) = (types.next(), types.next()) {
+                    return Some((key_type, value_type));
                 }
             }
         }
@@ -0,0 +1,6 @@
+/// Invalid `#[cdefault(...)]` attributes are reported at the attribute, see the `.stderr` next to each case.
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,70 @@
+use choreo_api::Defaultable;
+use default_derive::Default as ChoreoDefault;
+use std::time::Duration;
+
+const DEFAULT_PORT: u16 = 8080;
+static DEFAULT_ZONES: [&str; 2] = ["eu-west-1a", "eu-west-1b"];
+
+fn default_image() -> String {
+    "nginx:1.27".to_string()
+}
+
+mod defaults {
+    pub fn replicas() -> u32 {
+        2
+    }
+}
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Deployment {
+    #[cdefault(fn = default_image)]
+    image: Option<String>,
+    #[cdefault(fn = defaults::replicas)]
+    replicas: Option<u32>,
+    #[cdefault(const = DEFAULT_PORT)]
+    port: Option<u16>,
+    #[cdefault(expr = "DEFAULT_ZONES.map(String::from).to_vec()")]
+    zones: Vec<String>,
+    #[cdefault(expr = "Duration::from_secs(30)")]
+    timeout: Option<Duration>,
+    #[cdefault(fn = default_image, when = "zero")]
+    sidecar: String,
+    #[cdefault(expr = "Duration::from_secs(5)", when = "zero")]
+    grace_period: Duration,
+}
+
+#[test]
+fn computed_defaults_are_applied() {
+    let mut deployment = Deployment::default();
+    deployment.apply_defaults();
+    assert_eq!(
+        deployment,
+        Deployment {
+            image: Some("nginx:1.27".to_string()),
+            replicas: Some(2),
+            port: Some(8080),
+            zones: vec!["eu-west-1a".to_string(), "eu-west-1b".to_string()],
+            timeout: Some(Duration::from_secs(30)),
+            sidecar: "nginx:1.27".to_string(),
+            grace_period: Duration::from_secs(5),
+        }
+    );
+}
+
+#[test]
+fn set_values_are_kept() {
+    let mut deployment = Deployment {
+        image: Some("redis".to_string()),
+        port: Some(0),
+        zones: vec!["us-east-1a".to_string()],
+        sidecar: "envoy".to_string(),
+        grace_period: Duration::from_millis(1),
+        ..Default::default()
+    };
+    deployment.apply_defaults();
+    assert_eq!(deployment.image.as_deref(), Some("redis"));
+    assert_eq!(deployment.port, Some(0));
+    assert_eq!(deployment.zones, ["us-east-1a"]);
+    assert_eq!(deployment.sidecar, "envoy");
+    assert_eq!(deployment.grace_period, Duration::from_millis(1));
+}
@@ -0,0 +1,109 @@
+use choreo_api::{DefaultContext, Defaultable};
+use default_derive::Default as ChoreoDefault;
+use serde_json::json;
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct PortSpec {
+    #[cdefault(from = "metadata.name")]
+    name: Option<String>,
+    #[cdefault(from = "values.port")]
+    port: Option<u16>,
+}
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct ServiceSpec {
+    #[cdefault(from = "metadata.name")]
+    service_name: Option<String>,
+    #[cdefault(from = "metadata.namespace", when = "zero")]
+    namespace: String,
+    #[cdefault(from = "metadata.labels.tier")]
+    tier: Option<String>,
+    #[cdefault(from = "apiVersion")]
+    api_version: Option<String>,
+    #[cdefault(from = "values.region")]
+    region: Option<String>,
+    #[cdefault(from = "values.zones")]
+    zones: Option<Vec<String>>,
+    ports: Vec<PortSpec>,
+}
+
+fn context() -> DefaultContext {
+    DefaultContext::new()
+        .with_api_version("example.com/v1")
+        .with_metadata(&json!({
+            "name": "web",
+            "namespace": "shop",
+            "labels": {"tier": "frontend"},
+        }))
+        .with_value("region", &"eu-west-1")
+        .with_value("zones", &["eu-west-1a", "eu-west-1b"])
+        .with_value("port", &8080)
+}
+
+#[test]
+fn defaults_are_taken_from_the_context() {
+    let mut spec = ServiceSpec {
+        ports: vec![PortSpec::default()],
+        ..Default::default()
+    };
+    spec.apply_defaults_with(&context());
+    assert_eq!(
+        spec,
+        ServiceSpec {
+            service_name: Some("web".to_string()),
+            namespace: "shop".to_string(),
+            tier: Some("frontend".to_string()),
+            api_version: Some("example.com/v1".to_string()),
+            region: Some("eu-west-1".to_string()),
+            zones: Some(vec!["eu-west-1a".to_string(), "eu-west-1b".to_string()]),
+            ports: vec![PortSpec {
+                name: Some("web".to_string()),
+                port: Some(8080),
+            }],
+        }
+    );
+}
+
+#[test]
+fn set_fields_are_kept() {
+    let mut spec = ServiceSpec {
+        service_name: Some("api".to_string()),
+        namespace: "default".to_string(),
+        ..Default::default()
+    };
+    spec.apply_defaults_with(&context());
+    assert_eq!(spec.service_name.as_deref(), Some("api"));
+    assert_eq!(spec.namespace, "default");
+}
+
+#[test]
+fn missing_context_values_leave_the_field_unset() {
+    let mut spec = ServiceSpec::default();
+    spec.apply_defaults();
+    assert_eq!(spec, ServiceSpec::default());
+
+    // A value of another type is treated as missing
+    let mut port = PortSpec::default();
+    port.apply_defaults_with(&DefaultContext::new().with_value("port", &"http"));
+    assert_eq!(port, PortSpec::default());
+}
+
+#[test]
+fn context_paths() {
+    let context = context();
+    assert_eq!(
+        context.get("metadata.labels.tier"),
+        Some(&json!("frontend"))
+    );
+    assert_eq!(
+        context.get("values.zones"),
+        Some(&json!(["eu-west-1a", "eu-west-1b"]))
+    );
+    assert_eq!(context.get("metadata.uid"), None);
+    assert_eq!(context.get("metadata.name.first"), None);
+    assert_eq!(context.resolve::<u16>("values.port"), Some(8080));
+    assert_eq!(context.resolve::<u16>("values.region"), None);
+
+    let context = DefaultContext::new().with_metadata(&json!({"name": null}));
+    assert_eq!(context.get("metadata.name"), None);
+}
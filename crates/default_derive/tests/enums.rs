@@ -0,0 +1,123 @@
+use choreo_api::{Defaultable, IsSet};
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault, Clone, Debug, PartialEq)]
+enum Protocol {
+    #[cdefault]
+    Unspecified,
+    Tcp,
+    Udp,
+}
+
+#[derive(ChoreoDefault, Clone, Debug, PartialEq)]
+enum Source {
+    Git {
+        url: String,
+        #[cdefault("main")]
+        branch: Option<String>,
+    },
+    #[cdefault]
+    Image(String),
+}
+
+#[derive(ChoreoDefault, Clone, Debug, PartialEq)]
+enum Strategy {
+    Recreate,
+    #[cdefault]
+    RollingUpdate {
+        #[cdefault(1)]
+        max_surge: Option<u32>,
+        #[cdefault("25%")]
+        max_unavailable: Option<String>,
+        #[cdefault(600, when = "zero")]
+        progress_deadline_seconds: u32,
+    },
+}
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Service {
+    #[cdefault(Protocol::Tcp)]
+    protocol: Option<Protocol>,
+    #[cdefault("enum=Udp")]
+    fallback: Option<Protocol>,
+    #[cdefault(Protocol::Udp, when = "zero")]
+    required_protocol: Protocol,
+    #[cdefault(Source::Git { url: "https://example.com".to_string(), branch: None })]
+    source: Option<Source>,
+}
+
+#[test]
+fn marked_variant_is_the_default() {
+    assert_eq!(Protocol::default(), Protocol::Unspecified);
+    assert_eq!(Source::default(), Source::Image(String::new()));
+}
+
+#[test]
+fn marked_variant_takes_its_field_defaults() {
+    let strategy = Strategy::default();
+    assert_eq!(
+        strategy,
+        Strategy::RollingUpdate {
+            max_surge: Some(1),
+            max_unavailable: Some("25%".to_string()),
+            progress_deadline_seconds: 600,
+        }
+    );
+    let mut defaulted = strategy.clone();
+    defaulted.apply_defaults();
+    assert_eq!(defaulted, strategy);
+    assert!(!strategy.is_set());
+    assert!(Strategy::Recreate.is_set());
+}
+
+#[test]
+fn marked_variant_is_not_set() {
+    assert!(!Protocol::Unspecified.is_set());
+    assert!(Protocol::Tcp.is_set());
+    assert!(!Source::Image("nginx".to_string()).is_set());
+}
+
+#[test]
+fn variant_defaults_are_applied() {
+    let mut service = Service::default();
+    service.apply_defaults();
+    assert_eq!(service.protocol, Some(Protocol::Tcp));
+    assert_eq!(service.fallback, Some(Protocol::Udp));
+    assert_eq!(service.required_protocol, Protocol::Udp);
+    // the constructed variant applies its own field defaults
+    assert_eq!(
+        service.source,
+        Some(Source::Git {
+            url: "https://example.com".to_string(),
+            branch: Some("main".to_string()),
+        })
+    );
+}
+
+#[test]
+fn set_variants_are_kept() {
+    let mut service = Service {
+        protocol: Some(Protocol::Unspecified),
+        required_protocol: Protocol::Tcp,
+        ..Default::default()
+    };
+    service.apply_defaults();
+    assert_eq!(service.protocol, Some(Protocol::Unspecified));
+    assert_eq!(service.required_protocol, Protocol::Tcp);
+}
+
+#[test]
+fn active_variant_fields_are_defaulted() {
+    let mut source = Source::Git {
+        url: "https://example.com".to_string(),
+        branch: None,
+    };
+    source.apply_defaults();
+    assert_eq!(
+        source,
+        Source::Git {
+            url: "https://example.com".to_string(),
+            branch: Some("main".to_string()),
+        }
+    );
+}
@@ -0,0 +1,52 @@
+use choreo_api::Defaultable;
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Port {
+    #[cdefault(80)]
+    number: Option<u32>,
+}
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Replicas(#[cdefault(1)] Option<u32>);
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Pair(#[cdefault("a")] Option<String>, Port);
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Spec<P> {
+    replicas: Replicas,
+    primary: P,
+    others: Vec<P>,
+}
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Wrapper<T>(T)
+where
+    T: Defaultable;
+
+#[test]
+fn generic_fields_are_defaulted() {
+    let mut spec = Spec::<Port> {
+        others: vec![Port::default(), Port { number: Some(443) }],
+        ..Default::default()
+    };
+    spec.apply_defaults();
+    assert_eq!(spec.replicas, Replicas(Some(1)));
+    assert_eq!(spec.primary.number, Some(80));
+    assert_eq!(
+        spec.others,
+        [Port { number: Some(80) }, Port { number: Some(443) }]
+    );
+}
+
+#[test]
+fn tuple_fields_are_defaulted() {
+    let mut pair = Pair::default();
+    pair.apply_defaults();
+    assert_eq!(pair, Pair(Some("a".to_string()), Port { number: Some(80) }));
+
+    let mut wrapper = Wrapper(Port::default());
+    wrapper.apply_defaults();
+    assert_eq!(wrapper.0.number, Some(80));
+}
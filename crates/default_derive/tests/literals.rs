@@ -0,0 +1,50 @@
+use choreo_api::Defaultable;
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Limits {
+    #[cdefault(-1)]
+    retries: Option<i32>,
+    #[cdefault(-0.5)]
+    offset: Option<f64>,
+    #[cdefault(-2)]
+    scale: Option<f32>,
+    #[cdefault(3)]
+    count: Option<u8>,
+    #[cdefault(1.5)]
+    ratio: Option<f64>,
+    #[cdefault("nginx")]
+    image: Option<String>,
+    #[cdefault(true)]
+    enabled: Option<bool>,
+}
+
+#[test]
+fn literal_defaults_are_applied() {
+    let mut limits = Limits::default();
+    limits.apply_defaults();
+    assert_eq!(
+        limits,
+        Limits {
+            retries: Some(-1),
+            offset: Some(-0.5),
+            scale: Some(-2.0),
+            count: Some(3),
+            ratio: Some(1.5),
+            image: Some("nginx".to_string()),
+            enabled: Some(true),
+        }
+    );
+}
+
+#[test]
+fn set_values_are_kept() {
+    let mut limits = Limits {
+        retries: Some(5),
+        enabled: Some(false),
+        ..Default::default()
+    };
+    limits.apply_defaults();
+    assert_eq!(limits.retries, Some(5));
+    assert_eq!(limits.enabled, Some(false));
+}
@@ -0,0 +1,44 @@
+use choreo_api::Defaultable;
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Inner {
+    #[cdefault(8080)]
+    port: Option<u32>,
+}
+
+fn inner() -> Inner {
+    Inner { port: None }
+}
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Outer {
+    plain: Inner,
+    #[cdefault(fn = inner)]
+    created: Option<Inner>,
+    #[cdefault(fn = inner)]
+    existing: Option<Inner>,
+    #[cdefault("none")]
+    optional: Option<Inner>,
+    items: Vec<Inner>,
+    #[cdefault(skip)]
+    skipped: Inner,
+}
+
+#[test]
+fn nested_defaults_are_applied() {
+    let mut outer = Outer {
+        existing: Some(inner()),
+        optional: Some(inner()),
+        items: vec![inner()],
+        ..Default::default()
+    };
+    outer.apply_defaults();
+    let defaulted = || Inner { port: Some(8080) };
+    assert_eq!(outer.plain, defaulted());
+    assert_eq!(outer.created, Some(defaulted()));
+    assert_eq!(outer.existing, Some(defaulted()));
+    assert_eq!(outer.optional, Some(defaulted()));
+    assert_eq!(outer.items, [defaulted()]);
+    assert_eq!(outer.skipped, inner());
+}
@@ -0,0 +1,132 @@
+use choreo_api::{DefaultContext, Defaultable, DefaultedField, FieldPath};
+use default_derive::Default as ChoreoDefault;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[derive(ChoreoDefault, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PortSpec {
+    #[cdefault("TCP")]
+    protocol: Option<String>,
+    port: u16,
+}
+
+#[derive(ChoreoDefault, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ServiceSpec {
+    #[cdefault(from = "metadata.name")]
+    service_name: Option<String>,
+    #[cdefault(1)]
+    replicas: Option<u32>,
+    #[cdefault(["eu-west-1a"])]
+    zones: Vec<String>,
+    ports: Vec<PortSpec>,
+}
+
+fn paths(spec: &mut ServiceSpec, context: &DefaultContext) -> Vec<String> {
+    spec.apply_defaults_report_with(context)
+        .unwrap()
+        .paths()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn changed_fields_are_reported() {
+    let mut spec = ServiceSpec {
+        replicas: Some(3),
+        ports: vec![
+            PortSpec {
+                protocol: Some("UDP".to_string()),
+                port: 53,
+            },
+            PortSpec {
+                protocol: None,
+                port: 80,
+            },
+        ],
+        ..Default::default()
+    };
+    let context = DefaultContext::new().with_metadata(&json!({"name": "web"}));
+    let report = spec.apply_defaults_report_with(&context).unwrap();
+    assert_eq!(
+        report.fields,
+        [
+            DefaultedField {
+                path: FieldPath::root().field("ports").index(1).field("protocol"),
+                old: json!(null),
+                new: json!("TCP"),
+            },
+            DefaultedField {
+                path: FieldPath::root().field("serviceName"),
+                old: json!(null),
+                new: json!("web"),
+            },
+            DefaultedField {
+                path: FieldPath::root().field("zones"),
+                old: json!([]),
+                new: json!(["eu-west-1a"]),
+            },
+        ]
+    );
+    assert_eq!(
+        report.to_string(),
+        "Field 'ports[1].protocol' defaulted to \"TCP\"\n\
+         Field 'serviceName' defaulted to \"web\"\n\
+         Field 'zones' defaulted from [] to [\"eu-west-1a\"]"
+    );
+}
+
+#[test]
+fn defaulting_is_idempotent() {
+    let context = DefaultContext::new().with_metadata(&json!({"name": "web"}));
+    let mut spec = ServiceSpec {
+        ports: vec![PortSpec::default()],
+        ..Default::default()
+    };
+    assert_eq!(
+        paths(&mut spec, &context),
+        ["ports[0].protocol", "replicas", "serviceName", "zones"]
+    );
+    let report = spec.apply_defaults_report_with(&context).unwrap();
+    assert!(report.is_empty());
+    assert_eq!(report.to_string(), "");
+}
+
+#[test]
+fn report_without_context() {
+    let mut spec = ServiceSpec::default();
+    let report = spec.apply_defaults_report().unwrap();
+    assert_eq!(
+        report.paths().map(ToString::to_string).collect::<Vec<_>>(),
+        ["replicas", "zones"]
+    );
+    assert_eq!(spec.service_name, None);
+}
+
+#[derive(ChoreoDefault, Debug, Default, Serialize)]
+struct Routes {
+    #[cdefault(1)]
+    weight: Option<u32>,
+    // Keys that are not strings can not be serialized to JSON
+    by_port: BTreeMap<(u16, u16), String>,
+}
+
+#[test]
+fn serialization_errors_are_returned() {
+    let mut routes = Routes {
+        by_port: BTreeMap::from([((80, 8080), "web".to_string())]),
+        ..Default::default()
+    };
+    let error = routes.apply_defaults_report().unwrap_err();
+    assert_eq!(error.to_string(), "key must be a string");
+    assert_eq!(routes.weight, None);
+}
+
+#[test]
+#[should_panic(expected = "the value `ports` of a DefaultContext must serialize to JSON")]
+fn context_values_must_serialize() {
+    let ports = BTreeMap::from([((80, 8080), "web")]);
+    let _ = DefaultContext::new().with_value("ports", &ports);
+}
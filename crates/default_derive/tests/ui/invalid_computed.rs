@@ -0,0 +1,11 @@
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault)]
+struct Deployment {
+    #[cdefault(expr = "Duration::from_secs(")]
+    timeout: Option<std::time::Duration>,
+    #[cdefault(fn = missing_function)]
+    image: Option<String>,
+}
+
+fn main() {}
@@ -0,0 +1,29 @@
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault)]
+struct WrongType {
+    #[cdefault("three")]
+    replicas: Option<u32>,
+}
+
+#[derive(ChoreoDefault)]
+struct UnknownOption {
+    #[cdefault(3, until = "zero")]
+    replicas: Option<u32>,
+}
+
+#[derive(ChoreoDefault)]
+struct UnknownWhen {
+    #[cdefault(3, when = "empty")]
+    replicas: Option<u32>,
+}
+
+#[derive(ChoreoDefault)]
+enum TwoDefaults {
+    #[cdefault]
+    A,
+    #[cdefault]
+    B,
+}
+
+fn main() {}
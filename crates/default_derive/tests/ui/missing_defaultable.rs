@@ -0,0 +1,14 @@
+use default_derive::Default as ChoreoDefault;
+
+struct Quantity(String);
+
+#[derive(ChoreoDefault)]
+struct ResourceSpec {
+    #[cdefault("1")]
+    name: Option<String>,
+    limit: Quantity,
+    #[cdefault(skip)]
+    request: Quantity,
+}
+
+fn main() {}
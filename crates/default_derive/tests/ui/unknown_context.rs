@@ -0,0 +1,9 @@
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault)]
+struct ServiceSpec {
+    #[cdefault(from = "parent.name")]
+    service_name: Option<String>,
+}
+
+fn main() {}
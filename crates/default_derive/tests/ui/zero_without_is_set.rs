@@ -0,0 +1,15 @@
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault)]
+enum Status {
+    Active,
+    Inactive,
+}
+
+#[derive(ChoreoDefault)]
+struct Service {
+    #[cdefault(Status::Inactive, when = "zero")]
+    status: Status,
+}
+
+fn main() {}
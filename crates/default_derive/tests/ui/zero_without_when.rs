@@ -0,0 +1,11 @@
+use default_derive::Default as ChoreoDefault;
+
+#[derive(ChoreoDefault)]
+struct Feature {
+    #[cdefault(true)]
+    enabled: bool,
+    #[cdefault(8080)]
+    port: u16,
+}
+
+fn main() {}
@@ -0,0 +1,59 @@
+use choreo_api::Defaultable;
+use default_derive::Default as ChoreoDefault;
+use std::collections::BTreeMap;
+
+#[derive(ChoreoDefault, Debug, Default, PartialEq)]
+struct Deployment {
+    #[cdefault(8080, when = "zero")]
+    port: u32,
+    #[cdefault("nginx", when = "zero")]
+    image: String,
+    #[cdefault(true, when = "zero")]
+    enabled: bool,
+    #[cdefault(["run", "--verbose"])]
+    args: Vec<String>,
+    #[cdefault({"tier": "web"})]
+    labels: BTreeMap<String, String>,
+    #[cdefault(3, when = "zero")]
+    replicas: Option<u32>,
+    #[cdefault(1)]
+    min_replicas: Option<u32>,
+}
+
+#[test]
+fn zero_values_are_replaced() {
+    let mut deployment = Deployment {
+        min_replicas: Some(0),
+        ..Default::default()
+    };
+    deployment.apply_defaults();
+    assert_eq!(
+        deployment,
+        Deployment {
+            port: 8080,
+            image: "nginx".to_string(),
+            enabled: true,
+            args: vec!["run".to_string(), "--verbose".to_string()],
+            labels: BTreeMap::from([("tier".to_string(), "web".to_string())]),
+            replicas: Some(3),
+            // `Option` fields only replace a missing value by default
+            min_replicas: Some(0),
+        }
+    );
+}
+
+#[test]
+fn set_values_are_kept() {
+    let mut deployment = Deployment {
+        port: 443,
+        image: "httpd".to_string(),
+        args: vec!["serve".to_string()],
+        replicas: Some(5),
+        ..Default::default()
+    };
+    deployment.apply_defaults();
+    assert_eq!(deployment.port, 443);
+    assert_eq!(deployment.image, "httpd");
+    assert_eq!(deployment.args, ["serve"]);
+    assert_eq!(deployment.replicas, Some(5));
+}
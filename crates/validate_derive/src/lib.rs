@@ -1,6 +1,10 @@
 extern crate proc_macro;
 
+mod parse;
 mod rules;
+mod struct_rules;
+mod suggest;
+mod transition;
 mod validate;
 
 use proc_macro::TokenStream;
@@ -0,0 +1,274 @@
+use crate::rules::{ValidationRule, RULE_REGISTRY};
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, Ident, Lit, LitStr, Token, UnOp};
+
+/// Rules holding the rules for the elements of a collection, e.g. `items(format = "dns1123_label")`
+const COLLECTION_RULES: [&str; 3] = ["items", "keys", "values"];
+
+/// RuleMeta is a single entry of the `#[cvalidate(...)]` attribute grammar, either a flag
+/// such as `required`, a `name = value` pair such as `min_length = 3`, `fn = check_name` or `custom = Semver`,
+/// a list such as `one_of = ["TCP", "UDP"]` or `one_of("TCP", "UDP")`, or the rules for
+/// the elements of a collection such as `items(max_length = 63)`.
+struct RuleMeta {
+    name: Ident,
+    value: Option<Expr>,
+    args: Option<Punctuated<Expr, Token![,]>>,
+    sub_rules: Option<Punctuated<RuleMeta, Token![,]>>,
+}
+
+impl Parse for RuleMeta {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // `parse_any` also accepts keywords, so `fn = check_name` parses as a rule name
+        let name = input.call(Ident::parse_any)?;
+        let mut value = None;
+        let mut args = None;
+        let mut sub_rules = None;
+        if COLLECTION_RULES.contains(&name.to_string().as_str()) {
+            let content;
+            syn::parenthesized!(content in input);
+            sub_rules = Some(content.parse_terminated(RuleMeta::parse, Token![,])?);
+        } else if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            match input.parse::<Expr>()? {
+                Expr::Array(array) => args = Some(array.elems),
+                expr => value = Some(expr),
+            }
+        } else if input.peek(syn::token::Paren) {
+            let content;
+            syn::parenthesized!(content in input);
+            args = Some(content.parse_terminated(Expr::parse, Token![,])?);
+        }
+        Ok(Self {
+            name,
+            value,
+            args,
+            sub_rules,
+        })
+    }
+}
+
+impl RuleMeta {
+    fn into_rule(self) -> syn::Result<ValidationRule> {
+        let operator = canonical_operator(&self.name.to_string());
+        let sub_rules = match self.sub_rules {
+            Some(sub_rules) => collect_rules(sub_rules)?,
+            None => Vec::new(),
+        };
+        // The value of `custom` is a Rust expression evaluating to a `choreo_api::Rule`
+        let (value, span) = match &self.value {
+            Some(expr) if operator == "custom" => (Some(quote!(#expr).to_string()), expr.span()),
+            Some(expr) => (Some(expr_to_value(expr)?), expr.span()),
+            None => (None, self.name.span()),
+        };
+        let expr = self
+            .value
+            .filter(|_| operator == "custom")
+            .map(|expr| quote!(#expr));
+        let args = match &self.args {
+            Some(args) => args.iter().map(expr_to_value).collect::<syn::Result<_>>()?,
+            None => Vec::new(),
+        };
+        Ok(ValidationRule {
+            operator,
+            value,
+            args,
+            span,
+            name_span: self.name.span(),
+            expr,
+            message: None,
+            message_span: self.name.span(),
+            code: None,
+            warn: false,
+            sub_rules,
+        })
+    }
+}
+
+/// Parse the rules declared in a single `#[cvalidate(...)]` attribute.
+///
+/// Two forms are accepted:
+/// - the attribute grammar: `#[cvalidate(min_length = 3, pattern = r"^[a-z]{1,3}$", fn = check_name)]`
+/// - the legacy string form: `#[cvalidate("minLength=3, pattern=^[a-z]+$")]`
+pub(crate) fn parse_rules(attr: &Attribute) -> syn::Result<Vec<ValidationRule>> {
+    if let Ok(lit) = attr.parse_args::<LitStr>() {
+        return parse_legacy_rules(&lit);
+    }
+    collect_rules(attr.parse_args_with(Punctuated::<RuleMeta, Token![,]>::parse_terminated)?)
+}
+
+/// Convert the entries of an attribute, or of a collection rule, into rules.
+///
+/// `message = "..."`, `code = "..."` and `warn` apply to every rule declared next to them.
+fn collect_rules(metas: Punctuated<RuleMeta, Token![,]>) -> syn::Result<Vec<ValidationRule>> {
+    let mut rules = Vec::new();
+    let mut message = None;
+    let mut code = None;
+    let mut warn = None;
+    for meta in metas {
+        match meta.name.to_string().as_str() {
+            "message" => message = Some(meta.into_rule()?),
+            "code" => code = Some(meta.into_rule()?),
+            "warn" => warn = Some(meta.into_rule()?),
+            _ => rules.push(meta.into_rule()?),
+        }
+    }
+
+    if let Some(warn) = &warn {
+        if warn.value.is_some() || !warn.args.is_empty() {
+            return Err(syn::Error::new(warn.span, "`warn` does not take a value"));
+        }
+    }
+
+    for option in [&message, &code, &warn].into_iter().flatten() {
+        if option.value.is_none() && option.operator != "warn" {
+            return Err(syn::Error::new(
+                option.span,
+                format!("`{}` requires a string value, e.g. `{} = \"...\"`", option.operator, option.operator),
+            ));
+        }
+        if rules.iter().all(|rule| FIELD_OPTIONS.contains(&rule.operator.as_str())) {
+            return Err(syn::Error::new(
+                option.span,
+                format!("`{}` requires a rule in the same `#[cvalidate(...)]` attribute", option.operator),
+            ));
+        }
+    }
+    for rule in &mut rules {
+        rule.message = message.as_ref().and_then(|message| message.value.clone());
+        if let Some(message) = &message {
+            rule.message_span = message.span;
+        }
+        rule.code = code.as_ref().and_then(|code| code.value.clone());
+        if warn.is_some() {
+            set_warn(rule);
+        }
+    }
+    Ok(rules)
+}
+
+/// Report the violations of `rule` as warnings, including those of its collection sub-rules.
+fn set_warn(rule: &mut ValidationRule) {
+    rule.warn = true;
+    rule.sub_rules.iter_mut().for_each(set_warn);
+}
+
+/// Parse the legacy string form, where rules are separated by `,` and values follow the first `=`.
+/// List arguments are separated by `|`, e.g. `one_of=TCP|UDP|SCTP`. Empty entries, e.g. after a
+/// trailing `,`, are ignored. `message`, `code` and `warn` are only available in the attribute grammar.
+fn parse_legacy_rules(lit: &LitStr) -> syn::Result<Vec<ValidationRule>> {
+    lit.value()
+        .split(',')
+        .filter(|rule| !rule.trim().is_empty())
+        .map(|rule| {
+            let mut parts = rule.splitn(2, '=');
+            let operator = parts.next().unwrap_or_default().trim().to_string();
+            let value = parts.next().map(|v| v.trim().to_string());
+            if ATTRIBUTE_OPTIONS.contains(&operator.as_str()) {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!(
+                        "`{operator}` is not supported in the legacy string form, \
+                         use the attribute grammar, e.g. `#[cvalidate(min_length = 3, {operator}{})]`",
+                        if operator == "warn" { "" } else { " = \"...\"" }
+                    ),
+                ));
+            }
+            let args = match &value {
+                Some(value) if value.contains('|') => {
+                    value.split('|').map(|arg| arg.trim().to_string()).collect()
+                }
+                _ => Vec::new(),
+            };
+            Ok(ValidationRule {
+                operator,
+                value,
+                args,
+                span: lit.span(),
+                name_span: lit.span(),
+                expr: None,
+                message: None,
+                message_span: lit.span(),
+                code: None,
+                warn: false,
+                sub_rules: Vec::new(),
+            })
+        })
+        .collect()
+}
+
+/// The snake_case rule names of the attribute grammar and the names used in the rule registry.
+const OPERATOR_ALIASES: [(&str, &str); 6] = [
+    ("min_length", "minLength"),
+    ("max_length", "maxLength"),
+    ("min_items", "minItems"),
+    ("max_items", "maxItems"),
+    ("unique_items", "uniqueItems"),
+    ("enum", "one_of"),
+];
+
+/// Entries of the attribute grammar that are not rules in the registry.
+const ATTRIBUTE_OPTIONS: [&str; 3] = ["message", "code", "warn"];
+
+/// Entries of the attribute grammar that decide how the field itself is validated:
+/// every field is validated through its `Validate` implementation, `dive` states it explicitly
+/// and `skip` excludes the field.
+pub(crate) const FIELD_OPTIONS: [&str; 2] = ["dive", "skip"];
+
+/// Map the snake_case rule names of the attribute grammar to the names used in the rule registry.
+fn canonical_operator(name: &str) -> String {
+    OPERATOR_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, operator)| operator)
+        .to_string()
+}
+
+/// Returns the name of `operator` as it is written in the attribute grammar, e.g. `min_length` for `minLength`.
+pub(crate) fn attribute_name(operator: &str) -> &str {
+    OPERATOR_ALIASES
+        .iter()
+        .find(|(alias, canonical)| *canonical == operator && *alias != "enum")
+        .map_or(operator, |(alias, _)| alias)
+}
+
+/// All names accepted in `#[cvalidate(...)]`, used to suggest a close match for an unknown rule.
+pub(crate) fn attribute_names() -> impl Iterator<Item = &'static str> {
+    RULE_REGISTRY
+        .keys()
+        .copied()
+        .chain(OPERATOR_ALIASES.iter().map(|(alias, _)| *alias))
+        .chain(COLLECTION_RULES)
+        .chain(ATTRIBUTE_OPTIONS)
+        .chain(FIELD_OPTIONS)
+}
+
+/// Convert the value of a `name = value` pair into the textual value the rule handlers expect.
+fn expr_to_value(expr: &Expr) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(expr_lit) => match &expr_lit.lit {
+            Lit::Str(lit) => Ok(lit.value()),
+            Lit::Int(lit) => Ok(lit.base10_digits().to_string()),
+            Lit::Float(lit) => Ok(lit.base10_digits().to_string()),
+            Lit::Bool(lit) => Ok(lit.value().to_string()),
+            lit => Err(syn::Error::new(lit.span(), "unsupported literal in `#[cvalidate(...)]`")),
+        },
+        Expr::Unary(expr_unary) if matches!(expr_unary.op, UnOp::Neg(_)) => {
+            Ok(format!("-{}", expr_to_value(&expr_unary.expr)?))
+        }
+        Expr::Path(expr_path) if expr_path.qself.is_none() => Ok(expr_path
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::")),
+        _ => Err(syn::Error::new(
+            expr.span(),
+            "expected a literal or a path as value in `#[cvalidate(...)]`",
+        )),
+    }
+}
@@ -3,32 +3,80 @@ use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ValidationRule {
     pub operator: String,
     pub value: Option<String>, // Optional because not all rules require a value (e.g., "required")
+    pub args: Vec<String>, // List arguments, e.g. the allowed values of `one_of = ["TCP", "UDP"]`
+    pub span: proc_macro2::Span, // Location of the value of the rule, or of its name when it has no value
+    pub name_span: proc_macro2::Span, // Location of the name of the rule, e.g. `min_length` in `min_length = 3`
+    pub expr: Option<TokenStream>, // The value as written for rules taking a Rust expression, e.g. `custom = Quantity::at_most("10Gi")`
+    pub message: Option<String>, // Message template reported on violation, the detail of the failure for `rule = "..."`
+    pub message_span: proc_macro2::Span, // Location of the message template, errors in it are reported there
+    pub code: Option<String>, // Code reported instead of the error kind, e.g. `code = "replicas_too_low"`
+    pub warn: bool, // Violations are reported as warnings, e.g. `#[cvalidate(warn, max_length = 63)]`
+    pub sub_rules: Vec<ValidationRule>, // Rules for the elements of a collection, e.g. `items(max_length = 63)`
 }
 
-// Implement ToTokens for ValidationRule
-impl ToTokens for ValidationRule {
-    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        let operator = &self.operator;
-        let value = match &self.value {
-            Some(val) => quote! { Some(#val.to_string()) },
-            None => quote! { None },
-        };
+/// FieldTarget is the field a validation rule is applied to.
+pub struct FieldTarget {
+    /// Name of the field as it appears in the field path, e.g. `type` for `r#type`
+    pub name: String,
+    /// Expression evaluating to the field value, e.g. `self.name`
+    pub access: TokenStream,
+    /// Expression evaluating to the `FieldPath` of the field, e.g. `path.field("name")`
+    pub path: TokenStream,
+}
 
-        tokens.extend(quote! {
-            ValidationRule {
-                operator: #operator.to_string(),
-                value: #value,
-            }
-        });
+impl FieldTarget {
+    /// Target the named field `ident` of `self`
+    pub fn named(ident: &proc_macro2::Ident) -> Self {
+        let name = ident.to_string().trim_start_matches("r#").to_string();
+        Self {
+            path: quote! { path.field(#name) },
+            name,
+            access: quote! { self.#ident },
+        }
+    }
+
+    /// Target the unnamed field at `index` of a tuple struct. Like serde, the single field of a
+    /// newtype is located at the path of the struct itself, the fields of a tuple at their index.
+    pub fn unnamed(index: usize, is_newtype: bool) -> Self {
+        let member = syn::Index::from(index);
+        Self {
+            path: if is_newtype {
+                quote! { path.clone() }
+            } else {
+                quote! { path.index(#index) }
+            },
+            name: index.to_string(),
+            access: quote! { self.#member },
+        }
+    }
+
+    /// Target a field that is bound by reference in a match pattern, e.g. the field of an enum variant
+    pub fn bound(name: String, binding: &proc_macro2::Ident) -> Self {
+        let name = name.trim_start_matches("r#").to_string();
+        Self {
+            path: quote! { path.field(#name) },
+            name,
+            access: quote! { (*#binding) },
+        }
+    }
+
+    /// Target an element of a collection field, bound by reference to `binding` in a loop
+    /// where `path` is the path of the element, e.g. `ports[2]`
+    pub fn element(name: String, binding: &proc_macro2::Ident) -> Self {
+        Self {
+            path: quote! { path.clone() },
+            name,
+            access: quote! { (*#binding) },
+        }
     }
 }
 
 pub type ValidationHandler =
-    fn(&ValidationRule, &proc_macro2::Ident, Option<String>, bool) -> TokenStream;
+    fn(&ValidationRule, &FieldTarget, Option<String>, bool) -> TokenStream;
 
 #[derive(Debug)]
 pub struct RuleInfo {
@@ -38,6 +86,39 @@ pub struct RuleInfo {
     pub any_type: bool,
 }
 
+/// Integer types that rules comparing against integer literals support.
+const INTEGER_TYPES: [&str; 12] = [
+    "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize",
+];
+
+/// Floating point types that the numeric rules support.
+const FLOAT_TYPES: [&str; 2] = ["f32", "f64"];
+
+/// Non-zero integer types, the numeric rules compare their primitive value, e.g. `u32` for `NonZeroU32`.
+const NON_ZERO_TYPES: [&str; 12] = [
+    "NonZeroI8", "NonZeroI16", "NonZeroI32", "NonZeroI64", "NonZeroI128", "NonZeroIsize",
+    "NonZeroU8", "NonZeroU16", "NonZeroU32", "NonZeroU64", "NonZeroU128", "NonZeroUsize",
+];
+
+/// Tolerance of `mo` on floats when none is given, per float type. It is relative: a value is a multiple
+/// when the quotient of the value and the divisor is within `tolerance * |quotient|` of a whole number,
+/// or within `tolerance` when the quotient is smaller than one.
+const DEFAULT_FLOAT_TOLERANCES: [(&str, &str); 2] = [("f32", "1e-6"), ("f64", "1e-9")];
+
+/// Formats supported by the `format` rule, each is checked by `choreo_api::format::is_<format>`.
+const FORMATS: [&str; 10] = [
+    "dns1123_label",
+    "dns1123_subdomain",
+    "ipv4",
+    "ipv6",
+    "cidr",
+    "uri",
+    "email",
+    "uuid",
+    "date_time",
+    "duration",
+];
+
 pub static RULE_REGISTRY: Lazy<HashMap<&'static str, RuleInfo>> = Lazy::new(|| {
     let mut registry: HashMap<&'static str, RuleInfo> = HashMap::new();
 
@@ -60,7 +141,9 @@ pub static RULE_REGISTRY: Lazy<HashMap<&'static str, RuleInfo>> = Lazy::new(|| {
                 handler: handle_numeric_comparison,
                 supported_types: {
                     let mut types = HashSet::new();
-                    types.extend(["i32", "u32", "i64", "u64", "f32", "f64"]);
+                    types.extend(INTEGER_TYPES);
+                    types.extend(FLOAT_TYPES);
+                    types.extend(NON_ZERO_TYPES);
                     types
                 },
                 option_only: false,
@@ -69,6 +152,20 @@ pub static RULE_REGISTRY: Lazy<HashMap<&'static str, RuleInfo>> = Lazy::new(|| {
         );
     }
 
+    registry.insert(
+        "finite",
+        RuleInfo {
+            handler: handle_numeric_comparison,
+            supported_types: {
+                let mut types = HashSet::new();
+                types.extend(FLOAT_TYPES);
+                types
+            },
+            option_only: false,
+            any_type: false,
+        },
+    );
+
     // string rules
     let operators = ["maxLength", "minLength"];
     for operator in &operators {
@@ -120,303 +217,903 @@ pub static RULE_REGISTRY: Lazy<HashMap<&'static str, RuleInfo>> = Lazy::new(|| {
         );
     }
 
-    // custom rule
+    // list uniqueness rules
+    for operator in ["uniqueItems", "list_map_keys"] {
+        registry.insert(
+            operator,
+            RuleInfo {
+                handler: handle_unique_items,
+                supported_types: {
+                    let mut types = HashSet::new();
+                    types.extend(["Vec"]);
+                    types
+                },
+                option_only: false,
+                any_type: false,
+            },
+        );
+    }
+
+    // well known string formats
     registry.insert(
-        "fn",
+        "format",
         RuleInfo {
-            handler: handle_custom_function,
+            handler: handle_format,
             supported_types: {
-                let types = HashSet::new();
+                let mut types = HashSet::new();
+                types.extend(["String"]);
                 types
             },
             option_only: false,
+            any_type: false,
+        },
+    );
+
+    // allowed values rule
+    registry.insert(
+        "one_of",
+        RuleInfo {
+            handler: handle_one_of,
+            supported_types: {
+                let mut types = HashSet::new();
+                // a `Vec` checks every item like `items(one_of = [...])`, see `generate_rule_validation`
+                types.extend(["String", "Vec"]);
+                types.extend(INTEGER_TYPES);
+                types
+            },
+            option_only: false,
+            any_type: false,
+        },
+    );
+
+    // expression rule
+    registry.insert(
+        "rule",
+        RuleInfo {
+            handler: handle_expression_rule,
+            supported_types: HashSet::new(),
+            option_only: false,
+            any_type: true,
+        },
+    );
+
+    // reusable rules implementing `choreo_api::Rule`, e.g. `custom = Semver`
+    registry.insert(
+        "custom",
+        RuleInfo {
+            handler: handle_custom_rule,
+            supported_types: HashSet::new(),
+            option_only: false,
+            any_type: true,
+        },
+    );
+
+    // deprecated fields are always reported as warnings
+    registry.insert(
+        "deprecated",
+        RuleInfo {
+            handler: handle_deprecated,
+            supported_types: HashSet::new(),
+            option_only: false,
+            any_type: true,
+        },
+    );
+
+    // transition markers, checked by `validate_transition_at` when an object is updated
+    for operator in ["immutable", "immutable_once_set"] {
+        registry.insert(
+            operator,
+            RuleInfo {
+                handler: handle_transition_marker,
+                supported_types: HashSet::new(),
+                option_only: false,
+                any_type: true,
+            },
+        );
+    }
+    for operator in ["increase_only", "decrease_only"] {
+        registry.insert(
+            operator,
+            RuleInfo {
+                handler: handle_transition_marker,
+                supported_types: {
+                    let mut types = HashSet::new();
+                    types.extend(INTEGER_TYPES);
+                    types.extend(FLOAT_TYPES);
+                    types.extend(NON_ZERO_TYPES);
+                    types
+                },
+                option_only: false,
+                any_type: false,
+            },
+        );
+    }
+
+    // custom rule
+    registry.insert(
+        "fn",
+        RuleInfo {
+            handler: handle_custom_function,
+            supported_types: HashSet::new(),
+            option_only: false,
             any_type: true,
         },
     );
     registry
 });
 
+
 fn handle_required(
-    _rule: &ValidationRule,
-    field_name: &proc_macro2::Ident,
+    rule: &ValidationRule,
+    field: &FieldTarget,
     _field_type: Option<String>,
     _is_option: bool,
 ) -> TokenStream {
+    let access = &field.access;
+    let error = generate_error(rule, field, quote! { Required }, None, None);
     quote! {
-        if self.#field_name.is_none() {
-            errors.push(format!("Field '{}' is required", stringify!(#field_name)));
+        if #access.is_none() {
+            errors.push(#error);
         }
     }
 }
 
+/// `fn = check_name`: call the method of the struct, its error only has to implement `Display`
+/// and becomes the message of the error.
 fn handle_custom_function(
     rule: &ValidationRule,
-    field_name: &proc_macro2::Ident,
+    field: &FieldTarget,
     _field_type: Option<String>,
     _is_option: bool,
 ) -> TokenStream {
     // Extract the custom function name from the rule
-    if let Some(custom_fn_name) = &rule.value {
-        let custom_fn_ident = syn::Ident::new(custom_fn_name, proc_macro2::Span::call_site());
-        // If the field is an Option, validate only if it's `Some`
+    let Some(custom_fn_name) = &rule.value else {
+        return generate_compile_error("Missing method name for `fn`, e.g. `fn = check_name`", rule, field);
+    };
+    // The method is resolved at the attribute, so a missing method is reported there
+    let custom_fn_ident = match syn::parse_str::<syn::Ident>(custom_fn_name) {
+        Ok(mut ident) => {
+            ident.set_span(rule.span);
+            ident
+        }
+        Err(_) => {
+            return generate_compile_error(
+                &format!("`fn` expects the name of a method of the struct, found `{custom_fn_name}`"),
+                rule,
+                field,
+            )
+        }
+    };
+    let error = generate_error(rule, field, quote! { Custom }, Some(custom_fn_name), None);
+    quote! {
+        if let Err(e) = self.#custom_fn_ident() {
+            errors.push(#error.with_message(::std::string::ToString::to_string(&e)));
+        }
+    }
+}
+
+/// `custom = Semver`: check the field with the `choreo_api::Rule` the expression evaluates to,
+/// the rule is named by `Rule::name` in the error.
+fn handle_custom_rule(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    _field_type: Option<String>,
+    is_option: bool,
+) -> TokenStream {
+    let Some(custom_rule) = &rule.expr else {
+        return generate_compile_error("Missing rule for `custom`, e.g. `custom = Semver`", rule, field);
+    };
+    let access = &field.access;
+    let error = generate_error(rule, field, quote! { Custom }, None, None);
+    let check = quote! {
+        let custom_rule = #custom_rule;
+        if let Err(message) = ::choreo_api::Rule::check(&custom_rule, item) {
+            errors.push(
+                #error
+                    .with_constraint(::choreo_api::Rule::name(&custom_rule))
+                    .with_message(message),
+            );
+        }
+    };
+    if is_option {
+        quote! {
+            if let Some(item) = &#access {
+                #check
+            }
+        }
+    } else {
+        quote! {
+            {
+                let item = &#access;
+                #check
+            }
+        }
+    }
+}
+
+/// `deprecated` or `deprecated = "use spec.replicas instead"`: warn when the field is set.
+fn handle_deprecated(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    _field_type: Option<String>,
+    _is_option: bool,
+) -> TokenStream {
+    let access = &field.access;
+    let error = generate_error(rule, field, quote! { Deprecated }, None, None);
+    let message = rule.value.as_ref().map(|message| quote! { .with_message(#message) });
+    quote! {
+        if ::choreo_api::IsSet::is_set(&#access) {
+            errors.push(#error #message.with_severity(::choreo_api::Severity::Warning));
+        }
+    }
+}
+
+/// Transition markers only compare the old and the new version of a field,
+/// see `transition::generate_transitions_for_field`.
+fn handle_transition_marker(
+    _rule: &ValidationRule,
+    _field: &FieldTarget,
+    _field_type: Option<String>,
+    _is_option: bool,
+) -> TokenStream {
+    quote! {}
+}
+
+fn handle_expression_rule(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    _field_type: Option<String>,
+    is_option: bool,
+) -> TokenStream {
+    let Some(source) = &rule.value else {
+        return generate_compile_error("Missing rule expression", rule, field);
+    };
+    let message = rule.message.as_deref().map(|message| (message, rule.message_span));
+    let expression_rule = match generate_expression_rule(source, message, rule.code.as_deref(), rule.warn, rule.span) {
+        Ok(expression_rule) => expression_rule,
+        Err(err) => return err.to_compile_error(),
+    };
+    // Rules referring to `oldSelf` are only checked on updates
+    if ::choreo_api::expr::Program::compile(source).is_ok_and(|program| program.is_transition()) {
+        return quote! {};
+    }
+
+    let access = &field.access;
+    let field_path = &field.path;
+    if is_option {
         quote! {
-            if let Err(e) = self.#custom_fn_ident() {
-                errors.push(format!(
-                    "Field '{}' failed custom validation '{}': {}",
-                    stringify!(#field_name),
-                    stringify!(#custom_fn_name),
-                    e
-                ));
+            if let Some(ref item) = #access {
+                #expression_rule
+                rule.validate_at(item, &#field_path, errors);
             }
         }
     } else {
-        // Return a compile error if the function name is missing
         quote! {
-            compile_error!(concat!(
-                "Missing function name for custom validation rule on field `",
-                stringify!(#field_name),
-                "`."
-            ));
+            {
+                #expression_rule
+                rule.validate_at(&#access, &#field_path, errors);
+            }
         }
     }
 }
 
+/// Generate the statements binding `rule` to the compiled `choreo_api::ExpressionRule`.
+/// The expression is checked when the macro is expanded, the compiled rule is cached in a static
+/// that is local to the generated validation.
+pub fn generate_expression_rule(
+    source: &str,
+    message: Option<(&str, proc_macro2::Span)>,
+    code: Option<&str>,
+    warn: bool,
+    span: proc_macro2::Span,
+) -> syn::Result<TokenStream> {
+    if let Err(err) = ::choreo_api::expr::Program::compile(source) {
+        return Err(syn::Error::new(span, format!("Invalid rule `{source}`: {err}")));
+    }
+    // The message is a template like the message of a field rule, rendered when the error is displayed
+    if let Some((message, span)) = message {
+        render_message("rule", message, None, span)?;
+    }
+    let message = message.map(|(message, _)| quote! { .with_message(#message) });
+    let code = code.map(|code| quote! { .with_code(#code) });
+    let severity = warn.then(|| quote! { .with_severity(::choreo_api::Severity::Warning) });
+    Ok(quote! {
+        static RULE: ::std::sync::OnceLock<::choreo_api::ExpressionRule> = ::std::sync::OnceLock::new();
+        let rule = RULE.get_or_init(|| {
+            ::choreo_api::ExpressionRule::new(#source)
+                .expect("rule is checked at compile time")
+                #message
+                #code
+                #severity
+        });
+    })
+}
+
 fn handle_pattern(
     rule: &ValidationRule,
-    field_name: &proc_macro2::Ident,
+    field: &FieldTarget,
     _field_type: Option<String>,
     is_option: bool,
 ) -> TokenStream {
     match &rule.value {
-        None => generate_compile_error("Missing threshold value", field_name),
-        Some(val) => generate_pattern_code(field_name, val, is_option),
-    }  
+        None => generate_compile_error("Missing threshold value", rule, field),
+        Some(val) => match regex::Regex::new(val) {
+            Ok(_) => generate_pattern_code(rule, field, val, is_option),
+            Err(err) => syn::Error::new(
+                rule.span,
+                format!("Invalid regex pattern `{}` for field `{}`: {}", val, field.name, err),
+            )
+            .to_compile_error(),
+        },
+    }
 }
 
-
 fn handle_length_comparison(
     rule: &ValidationRule,
-    field_name: &proc_macro2::Ident,
+    field: &FieldTarget,
     _field_type: Option<String>,
     is_option: bool,
 ) -> TokenStream {
-    let threshold = if let Some(ref value_str) = rule.value {
-        parse_threshold(value_str, Some("usize"), field_name)
-    } else {
-        return generate_compile_error("Missing threshold value", field_name);
+    let Some(ref value_str) = rule.value else {
+        return generate_compile_error("Missing threshold value", rule, field);
     };
-
-    let comparison_code = match rule.operator.as_str() {
-        "minLength" => generate_length_comparison_code(field_name, &threshold, is_option, quote! { < }, quote! { >= }),
-        "maxLength" => generate_length_comparison_code(field_name, &threshold, is_option, quote! { > }, quote! { <= }),
-        "minItems" => generate_length_comparison_code(field_name, &threshold, is_option, quote! { < }, quote! { >= }),
-        "maxItems" => generate_length_comparison_code(field_name, &threshold, is_option, quote! { > }, quote! { <= }),
-        _ => {
-            return generate_compile_error("Invalid operator", field_name);
-        }
+    let threshold = match parse_threshold(value_str, Some("usize"), rule, field) {
+        Ok(threshold) => threshold,
+        Err(err) => return err,
     };
 
-    comparison_code
+    match rule.operator.as_str() {
+        "minLength" => generate_length_comparison_code(rule, field, &threshold, is_option, quote! { < }, format!(">= {value_str}"), quote! { TooShort }),
+        "maxLength" => generate_length_comparison_code(rule, field, &threshold, is_option, quote! { > }, format!("<= {value_str}"), quote! { TooLong }),
+        "minItems" => generate_length_comparison_code(rule, field, &threshold, is_option, quote! { < }, format!(">= {value_str}"), quote! { TooFewItems }),
+        "maxItems" => generate_length_comparison_code(rule, field, &threshold, is_option, quote! { > }, format!("<= {value_str}"), quote! { TooManyItems }),
+        _ => generate_compile_error("Invalid operator", rule, field),
+    }
 }
 
 fn handle_numeric_comparison(
     rule: &ValidationRule,
-    field_name: &proc_macro2::Ident,
+    field: &FieldTarget,
     field_type: Option<String>,
     is_option: bool,
 ) -> TokenStream {
-    let threshold = if let Some(ref value_str) = rule.value {
-        parse_threshold(value_str, field_type.as_deref(), field_name)
+    let Some(field_type) = field_type else {
+        return generate_compile_error("Unsupported field type", rule, field);
+    };
+    // `NonZero*` values are compared by their primitive value
+    let is_non_zero = NON_ZERO_TYPES.contains(&field_type.as_str());
+    let primitive_type = primitive_type(&field_type);
+
+    if rule.operator == "finite" {
+        let error = generate_error(rule, field, quote! { OutOfRange }, Some("finite"), Some(quote! { item }));
+        let check = quote! {
+            if !item.is_finite() {
+                errors.push(#error);
+            }
+        };
+        return generate_numeric_check_code(field, is_option, is_non_zero, check);
+    }
+
+    // `mo` accepts a tolerance for floats as second argument, e.g. `mo(0.1, 1e-6)`
+    let value_str = match (&rule.value, rule.args.first()) {
+        (Some(value_str), _) | (None, Some(value_str)) => value_str,
+        (None, None) => return generate_compile_error("Missing threshold value", rule, field),
+    };
+    let threshold = match parse_threshold(value_str, Some(&primitive_type), rule, field) {
+        Ok(threshold) => threshold,
+        Err(err) => return err,
+    };
+
+    match rule.operator.as_str() {
+        "ge" => generate_number_comparison_code(rule, field, &threshold, is_option, is_non_zero, quote! { < }, format!(">= {value_str}")),
+        "gt" => generate_number_comparison_code(rule, field, &threshold, is_option, is_non_zero, quote! { <= }, format!("> {value_str}")),
+        "le" => generate_number_comparison_code(rule, field, &threshold, is_option, is_non_zero, quote! { > }, format!("<= {value_str}")),
+        "lt" => generate_number_comparison_code(rule, field, &threshold, is_option, is_non_zero, quote! { >= }, format!("< {value_str}")),
+        "mo" => {
+            if value_str.parse::<f64>().is_ok_and(|divisor| divisor == 0.0) {
+                return generate_compile_error("`mo` requires a divisor other than zero", rule, field);
+            }
+            let is_float = FLOAT_TYPES.contains(&primitive_type.as_str());
+            let tolerance = match rule.args.get(1) {
+                Some(_) if !is_float => return generate_compile_error("`mo` only accepts a tolerance for floats", rule, field),
+                Some(tolerance) => tolerance.as_str(),
+                None => DEFAULT_FLOAT_TOLERANCES
+                    .iter()
+                    .find(|(float_type, _)| *float_type == primitive_type)
+                    .map_or("0", |(_, tolerance)| tolerance),
+            };
+            let tolerance = match is_float.then(|| parse_threshold(tolerance, Some(&primitive_type), rule, field)) {
+                Some(Ok(tolerance)) => Some(tolerance),
+                Some(Err(err)) => return err,
+                None => None,
+            };
+            generate_modulo_code(rule, field, &threshold, tolerance, is_option, is_non_zero, value_str)
+        }
+        _ => generate_compile_error("Invalid operator", rule, field),
+    }
+}
+
+/// Returns the primitive type the numeric rules compare a value of `field_type` as,
+/// e.g. `u32` for `NonZeroU32`.
+fn primitive_type(field_type: &str) -> String {
+    match field_type.strip_prefix("NonZero") {
+        Some(primitive) if NON_ZERO_TYPES.contains(&field_type) => primitive.to_lowercase(),
+        _ => field_type.to_string(),
+    }
+}
+
+fn handle_unique_items(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    _field_type: Option<String>,
+    is_option: bool,
+) -> TokenStream {
+    // The items, or their keys, are hashed when they implement `Hash`, see `choreo_api::unique`
+    let (keys, message) = if rule.operator == "list_map_keys" {
+        let keys = match parse_list_map_keys(rule) {
+            Ok(keys) => keys,
+            Err(err) => return err.to_compile_error(),
+        };
+        let message = format!(
+            "same {}",
+            keys.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+        );
+        let keys = quote! {
+            let keys = items.iter().map(|item| (#(&item.#keys,)*)).collect::<::std::vec::Vec<_>>();
+            let keys = keys.as_slice();
+        };
+        (keys, Some(message))
     } else {
-        return generate_compile_error("Missing threshold value", field_name);
+        if rule.value.as_deref().is_some_and(|value| value != "true") {
+            return quote! {};
+        }
+        (quote! { let keys = items.as_slice(); }, None)
     };
 
-    let comparison_code = match rule.operator.as_str() {
-        "ge" => generate_number_comparison_code(field_name, &threshold, is_option, quote! { < }, quote! { >= }),
-        "gt" => generate_number_comparison_code(field_name, &threshold, is_option, quote! { <= }, quote! { > }),
-        "le" => generate_number_comparison_code(field_name, &threshold, is_option, quote! { > }, quote! { <= }),
-        "lt" => generate_number_comparison_code(field_name, &threshold, is_option, quote! { >= }, quote! { < }),
-        "mo" => generate_modulo_code(field_name, &threshold, is_option),
-        _ => {
-            return generate_compile_error("Invalid operator", field_name);
+    let access = &field.access;
+    let field_path = &field.path;
+    let error = generate_error_at(
+        rule,
+        quote! { #field_path.index(index) },
+        quote! { Duplicate },
+        None,
+        None,
+    );
+    let message = message.map(|message| quote! { .with_message(#message) });
+    // Every item equal to an earlier item is reported, located at its own index
+    let check = quote! {
+        {
+            use ::choreo_api::unique::{ComparedDuplicates as _, HashedDuplicates as _};
+            #keys
+            for (index, first) in (&::choreo_api::unique::Duplicates(keys)).find() {
+                errors.push(
+                    #error
+                        .with_constraint(format!("'{}'", #field_path.index(first)))
+                        #message
+                );
+            }
         }
     };
+    if is_option {
+        quote! {
+            if let Some(ref items) = #access {
+                #check
+            }
+        }
+    } else {
+        quote! {
+            {
+                let items = &#access;
+                #check
+            }
+        }
+    }
+}
 
-    comparison_code
+/// Parse the key fields of `list_map_keys = "name,protocol"` or `list_map_keys = ["name", "protocol"]`.
+pub(crate) fn parse_list_map_keys(rule: &ValidationRule) -> syn::Result<Vec<syn::Ident>> {
+    let keys: Vec<String> = if rule.args.is_empty() {
+        rule.value
+            .iter()
+            .flat_map(|value| value.split(','))
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .collect()
+    } else {
+        rule.args.clone()
+    };
+    if keys.is_empty() {
+        return Err(syn::Error::new(rule.span, "`list_map_keys` requires at least one key field"));
+    }
+    keys.iter()
+        .map(|key| {
+            syn::parse_str::<syn::Ident>(key)
+                .map(|mut ident| {
+                    ident.set_span(rule.span);
+                    ident
+                })
+                .map_err(|_| syn::Error::new(rule.span, format!("Invalid key field `{key}` in `list_map_keys`")))
+        })
+        .collect()
 }
 
-/// Generate comparison logic dynamically based on operator.
-fn generate_length_comparison_code(
-    field_name: &proc_macro2::Ident,
-    threshold: &TokenStream,
+fn handle_format(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    _field_type: Option<String>,
     is_option: bool,
-    invalid_op: TokenStream,
-    valid_op: TokenStream,
 ) -> TokenStream {
+    let Some(format) = &rule.value else {
+        return generate_compile_error("Missing format", rule, field);
+    };
+    if !FORMATS.contains(&format.as_str()) {
+        return syn::Error::new(
+            rule.span,
+            format!(
+                "Unknown format `{}` for field `{}`, supported formats are: {}",
+                format,
+                field.name,
+                FORMATS.join(", ")
+            ),
+        )
+        .to_compile_error();
+    }
+
+    let access = &field.access;
+    let check_fn = syn::Ident::new(&format!("is_{format}"), rule.span);
+    let error = generate_error(rule, field, quote! { InvalidFormat }, Some(format), Some(quote! { item }));
     if is_option {
         quote! {
-            if let Some(ref item) = self.#field_name {
-                if item.len() #invalid_op #threshold {
-                    errors.push(format!(
-                        "Field '{}' length must be {} {}.",
-                        stringify!(#field_name),
-                        stringify!(#valid_op),
-                        stringify!(#threshold),
-                    ));
+            if let Some(ref item) = #access {
+                if !::choreo_api::format::#check_fn(item) {
+                    errors.push(#error);
                 }
             }
         }
     } else {
         quote! {
-            if self.#field_name.len() #invalid_op #threshold {
-                errors.push(format!(
-                    "Field '{}' length must be {} {}.",
-                    stringify!(#field_name),
-                    stringify!(#valid_op),
-                    stringify!(#threshold),
-                ));
+            {
+                let item = &#access;
+                if !::choreo_api::format::#check_fn(item) {
+                    errors.push(#error);
+                }
             }
         }
     }
 }
 
-/// Generate comparison logic dynamically based on operator.
-fn generate_number_comparison_code(
-    field_name: &proc_macro2::Ident,
+fn handle_one_of(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    field_type: Option<String>,
+    is_option: bool,
+) -> TokenStream {
+    // The allowed values are given as a list, a single value is allowed as well
+    let allowed = if rule.args.is_empty() {
+        rule.value.iter().cloned().collect::<Vec<_>>()
+    } else {
+        rule.args.clone()
+    };
+    if allowed.is_empty() {
+        return generate_compile_error("Missing allowed values", rule, field);
+    }
+    let constraint = format!("[{}]", allowed.join(", "));
+
+    let is_integer = field_type
+        .as_deref()
+        .is_some_and(|field_type| INTEGER_TYPES.contains(&field_type));
+    let allowed_values = if is_integer {
+        let mut values = Vec::new();
+        for value in &allowed {
+            match value.parse::<i128>() {
+                Ok(_) => values.push(syn::LitInt::new(value, rule.span).to_token_stream()),
+                Err(_) => {
+                    return syn::Error::new(
+                        rule.span,
+                        format!("Allowed value `{}` for field `{}` is not an integer", value, field.name),
+                    )
+                    .to_compile_error()
+                }
+            }
+        }
+        quote! { [#(#values),*] }
+    } else {
+        quote! { [#(#allowed),*] }
+    };
+
+    let access = &field.access;
+    let error = generate_error(rule, field, quote! { NotOneOf }, Some(&constraint), Some(quote! { item }));
+    let item = if is_integer { quote! { item } } else { quote! { &item.as_str() } };
+    let check = quote! {
+        if !#allowed_values.contains(#item) {
+            errors.push(#error);
+        }
+    };
+
+    if is_option {
+        quote! {
+            if let Some(ref item) = #access {
+                #check
+            }
+        }
+    } else {
+        quote! {
+            {
+                let item = &#access;
+                #check
+            }
+        }
+    }
+}
+
+/// Generate length comparison logic dynamically based on operator.
+fn generate_length_comparison_code(
+    rule: &ValidationRule,
+    field: &FieldTarget,
     threshold: &TokenStream,
     is_option: bool,
     invalid_op: TokenStream,
-    valid_op: TokenStream,
+    constraint: String,
+    kind: TokenStream,
 ) -> TokenStream {
+    let access = &field.access;
+    let error = generate_error(rule, field, kind, Some(&constraint), Some(quote! { item.len() }));
     if is_option {
         quote! {
-            if let Some(item) = self.#field_name {
-                if item #invalid_op #threshold {
-                    errors.push(format!(
-                        "Field '{}' must be {} {}.",
-                        stringify!(#field_name),
-                        stringify!(#valid_op),
-                        stringify!(#threshold),
-                    ));
+            if let Some(ref item) = #access {
+                if item.len() #invalid_op #threshold {
+                    errors.push(#error);
                 }
             }
         }
     } else {
         quote! {
-            if self.#field_name #invalid_op #threshold {
-                errors.push(format!(
-                    "Field '{}' must be {} {}.",
-                    stringify!(#field_name),
-                    stringify!(#valid_op),
-                    stringify!(#threshold),
-                ));
+            {
+                let item = &#access;
+                if item.len() #invalid_op #threshold {
+                    errors.push(#error);
+                }
             }
         }
     }
 }
 
+/// Generate number comparison logic dynamically based on operator.
+fn generate_number_comparison_code(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    threshold: &TokenStream,
+    is_option: bool,
+    is_non_zero: bool,
+    invalid_op: TokenStream,
+    constraint: String,
+) -> TokenStream {
+    let error = generate_error(rule, field, quote! { OutOfRange }, Some(&constraint), Some(quote! { item }));
+    let check = quote! {
+        if item #invalid_op #threshold {
+            errors.push(#error);
+        }
+    };
+    generate_numeric_check_code(field, is_option, is_non_zero, check)
+}
 
-/// Generate modulo logic for "mo" operator.
+/// Generate modulo logic for "mo" operator. Floats are a multiple when the quotient
+/// is within `tolerance` of a whole number.
 fn generate_modulo_code(
-    field_name: &proc_macro2::Ident,
+    rule: &ValidationRule,
+    field: &FieldTarget,
     threshold: &TokenStream,
+    tolerance: Option<TokenStream>,
+    is_option: bool,
+    is_non_zero: bool,
+    constraint: &str,
+) -> TokenStream {
+    let error = generate_error(rule, field, quote! { NotMultipleOf }, Some(constraint), Some(quote! { item }));
+    let is_multiple = match tolerance {
+        Some(tolerance) => quote! {
+            {
+                // The rounding error of the quotient grows with its magnitude, so the tolerance does too
+                let quotient = item / #threshold;
+                (quotient - quotient.round()).abs() <= #tolerance * quotient.abs().max(1.0)
+            }
+        },
+        None => quote! { (item % #threshold == 0) },
+    };
+    let check = quote! {
+        if !#is_multiple {
+            errors.push(#error);
+        }
+    };
+    generate_numeric_check_code(field, is_option, is_non_zero, check)
+}
+
+/// Wrap a numeric `check` so that `item` is bound to the value of the field,
+/// unwrapping `Option` and the primitive value of `NonZero*` types.
+fn generate_numeric_check_code(
+    field: &FieldTarget,
     is_option: bool,
+    is_non_zero: bool,
+    check: TokenStream,
 ) -> TokenStream {
+    let access = &field.access;
+    let get = is_non_zero.then(|| quote! { let item = item.get(); });
     if is_option {
         quote! {
-            if let Some(item) = self.#field_name {
-                if item % #threshold != 0 {
-                    errors.push(format!(
-                        "Field '{}' must be a multiple of {}.",
-                        stringify!(#field_name),
-                        stringify!(#threshold),
-                    ));
-                }
+            if let Some(item) = #access {
+                #get
+                #check
             }
         }
     } else {
         quote! {
-            if self.#field_name % #threshold != 0 {
-                errors.push(format!(
-                    "Field '{}' must be a multiple of {}.",
-                    stringify!(#field_name),
-                    stringify!(#threshold),
-                ));
+            {
+                let item = #access;
+                #get
+                #check
             }
         }
     }
 }
 
+/// Generate pattern matching logic. The pattern is checked when the macro is expanded,
+/// the compiled regex is cached in a static that is local to the generated field validation.
 fn generate_pattern_code(
-    field_name: &proc_macro2::Ident,
+    rule: &ValidationRule,
+    field: &FieldTarget,
     regex_pattern: &str,
     is_option: bool,
 ) -> TokenStream {
+    let access = &field.access;
+    let error = generate_error(rule, field, quote! { PatternMismatch }, Some(regex_pattern), Some(quote! { item }));
+    // The regex is compiled once in a function item, so code checking the items of a collection
+    // in a loop does not trip `clippy::regex_creation_in_loops`
+    let regex = quote! {
+        fn regex() -> &'static ::regex::Regex {
+            static REGEX: ::std::sync::OnceLock<::regex::Regex> = ::std::sync::OnceLock::new();
+            REGEX.get_or_init(|| {
+                ::regex::Regex::new(#regex_pattern).expect("pattern is checked at compile time")
+            })
+        }
+        let regex = regex();
+    };
     if is_option {
         quote! {
-            if let Some(ref item) = self.#field_name {
-                let regex = regex::Regex::new(#regex_pattern).expect("Invalid regex pattern");
-                if !regex.is_match(&item) {
-                    errors.push(format!(
-                        "Field '{}' does not match the required pattern: '{}'.",
-                        stringify!(#field_name),
-                        #regex_pattern,
-                    ));
+            if let Some(ref item) = #access {
+                #regex
+                if !regex.is_match(item) {
+                    errors.push(#error);
                 }
             }
         }
     } else {
         quote! {
-            let regex = regex::Regex::new(#regex_pattern).expect("Invalid regex pattern");
-            if !regex.is_match(&self.#field_name) {
-                errors.push(format!(
-                    "Field '{}' does not match the required pattern: '{}'.",
-                    stringify!(#field_name),
-                    #regex_pattern,
-                ));
+            {
+                #regex
+                let item = &#access;
+                if !regex.is_match(item) {
+                    errors.push(#error);
+                }
             }
         }
     }
 }
 
-/// Parse the threshold value based on the field type.
+/// Generate the expression that builds a `ValidationError` of `kind` for the field violating `rule`.
+/// `value` is an expression evaluating to the offending value, if any.
+pub(crate) fn generate_error(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    kind: TokenStream,
+    constraint: Option<&str>,
+    value: Option<TokenStream>,
+) -> TokenStream {
+    generate_error_at(rule, field.path.clone(), kind, constraint, value)
+}
+
+/// Generate the expression that builds a `ValidationError` of `kind` located at the `FieldPath` expression `path`.
+/// The `message` and `code` of the rule replace the default text and the kind.
+fn generate_error_at(
+    rule: &ValidationRule,
+    path: TokenStream,
+    kind: TokenStream,
+    constraint: Option<&str>,
+    value: Option<TokenStream>,
+) -> TokenStream {
+    let constraint = constraint.map(|c| quote! { .with_constraint(#c) });
+    let value = value.map(|v| quote! { .with_value(#v) });
+    let template = match rule.message.as_deref().map(|message| render_limit(rule, message)) {
+        Some(Ok(template)) => Some(quote! { .with_template(#template) }),
+        Some(Err(err)) => return err.to_compile_error(),
+        None => None,
+    };
+    let code = rule.code.as_ref().map(|code| quote! { .with_code(#code) });
+    let severity = rule.warn.then(|| quote! { .with_severity(::choreo_api::Severity::Warning) });
+    quote! {
+        ::choreo_api::ValidationError::new(#path, ::choreo_api::ValidationErrorKind::#kind)
+            #constraint
+            #value
+            #template
+            #code
+            #severity
+    }
+}
+
+/// Replace the `{limit}` placeholder of a message template by the limit of the rule,
+/// the other placeholders are checked and rendered when the error is displayed.
+fn render_limit(rule: &ValidationRule, template: &str) -> syn::Result<String> {
+    // `mo(0.5, 1e-6)` is limited by the divisor, `one_of("a", "b")` by all of the allowed values
+    let limit = match (&rule.value, rule.args.as_slice()) {
+        (Some(value), _) => Some(value.clone()),
+        (None, []) => None,
+        (None, [divisor, ..]) if rule.operator == "mo" => Some(divisor.clone()),
+        (None, args) => Some(args.join(", ")),
+    };
+    render_message(&rule.operator, template, limit, rule.message_span)
+}
+
+/// Replace the `{limit}` placeholder of the message template of `operator` when there is a limit,
+/// failing at `span` on unknown placeholders.
+fn render_message(operator: &str, template: &str, limit: Option<String>, span: proc_macro2::Span) -> syn::Result<String> {
+    ::choreo_api::validation_error::render_template(template, |name| match name {
+        "limit" => limit.as_ref().map(|limit| limit.replace('{', "{{").replace('}', "}}")),
+        name if ::choreo_api::validation_error::TEMPLATE_PLACEHOLDERS.contains(&name) => {
+            Some(format!("{{{name}}}"))
+        }
+        _ => None,
+    })
+    .map_err(|err| {
+        let available = if limit.is_some() { "limit, " } else { "" };
+        syn::Error::new(
+            span,
+            format!(
+                "Invalid `message` of `{}`: {err}, expected one of: {available}{}",
+                operator,
+                ::choreo_api::validation_error::TEMPLATE_PLACEHOLDERS.join(", ")
+            ),
+        )
+    })
+}
+
+/// Parse the threshold value based on the field type, an invalid value is returned as compile error.
 fn parse_threshold(
     value_str: &str,
     field_type: Option<&str>,
-    field_name: &proc_macro2::Ident,
-) -> TokenStream {
+    rule: &ValidationRule,
+    field: &FieldTarget,
+) -> Result<TokenStream, TokenStream> {
     match field_type {
-        Some("u32") => parse_threshold_value::<u32>(value_str, field_name, "u32"),
-        Some("i32") => parse_threshold_value::<i32>(value_str, field_name, "i32"),
-        Some("u64") => parse_threshold_value::<u64>(value_str, field_name, "u64"),
-        Some("i64") => parse_threshold_value::<i64>(value_str, field_name, "i64"),
-        Some("f32") | Some("f64") => parse_threshold_value::<f64>(value_str, field_name, "float"),
-        Some("usize") => parse_threshold_value::<usize>(value_str, field_name, "usize"),
-        _ => generate_compile_error("Unsupported field type", field_name),
+        Some("i8") => parse_threshold_value::<i8>(value_str, rule, field, "i8"),
+        Some("i16") => parse_threshold_value::<i16>(value_str, rule, field, "i16"),
+        Some("i32") => parse_threshold_value::<i32>(value_str, rule, field, "i32"),
+        Some("i64") => parse_threshold_value::<i64>(value_str, rule, field, "i64"),
+        Some("i128") => parse_threshold_value::<i128>(value_str, rule, field, "i128"),
+        Some("isize") => parse_threshold_value::<isize>(value_str, rule, field, "isize"),
+        Some("u8") => parse_threshold_value::<u8>(value_str, rule, field, "u8"),
+        Some("u16") => parse_threshold_value::<u16>(value_str, rule, field, "u16"),
+        Some("u32") => parse_threshold_value::<u32>(value_str, rule, field, "u32"),
+        Some("u64") => parse_threshold_value::<u64>(value_str, rule, field, "u64"),
+        Some("u128") => parse_threshold_value::<u128>(value_str, rule, field, "u128"),
+        Some("usize") => parse_threshold_value::<usize>(value_str, rule, field, "usize"),
+        Some("f32") => parse_threshold_value::<f32>(value_str, rule, field, "f32"),
+        Some("f64") => parse_threshold_value::<f64>(value_str, rule, field, "f64"),
+        _ => Err(generate_compile_error("Unsupported field type", rule, field)),
     }
 }
 
-
 /// Helper to parse a threshold value for a specific type.
 fn parse_threshold_value<T: std::str::FromStr + quote::ToTokens>(
     value_str: &str,
-    field_name: &proc_macro2::Ident,
-    _expected_type: &str,
-) -> TokenStream {
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    expected_type: &str,
+) -> Result<TokenStream, TokenStream> {
     match value_str.parse::<T>() {
-        Ok(val) => quote! { #val },
-        Err(_) => generate_compile_error(value_str, field_name),
+        Ok(val) => Ok(quote! { #val }),
+        Err(_) => Err(generate_compile_error(&format!("Invalid `{expected_type}` value `{value_str}`"), rule, field)),
     }
 }
 
-/// Generate a compile-time error for invalid threshold values.
-fn generate_compile_error(message: &str, field_name: &proc_macro2::Ident) -> TokenStream {
-    quote! {
-        compile_error!(concat!(
-            #message,
-            " for field `",
-            stringify!(#field_name),
-            "`."
-        ));
-    }
-}
\ No newline at end of file
+/// Generate a compile-time error located at the value of the rule, or at its name when it has no value.
+fn generate_compile_error(message: &str, rule: &ValidationRule, field: &FieldTarget) -> TokenStream {
+    syn::Error::new(rule.span, format!("{message} for field `{}`", field.name)).to_compile_error()
+}
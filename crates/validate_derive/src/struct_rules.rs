@@ -0,0 +1,416 @@
+use crate::rules::generate_expression_rule;
+use crate::suggest::{did_you_mean, suggestion};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{BinOp, Expr, Fields, Ident, LitStr, Token, Type};
+
+/// Names of the struct level rules, `message`, `code` and `warn` qualify a `rule`.
+const STRUCT_RULES: [&str; 8] = [
+    "mutually_exclusive",
+    "at_least_one_of",
+    "required_if",
+    "compare",
+    "rule",
+    "message",
+    "code",
+    "warn",
+];
+
+/// StructRule is a cross-field rule declared with `#[cvalidate(...)]` on the struct itself.
+pub(crate) enum StructRule {
+    /// `mutually_exclusive(a, b, ...)`: at most one of the fields may be set
+    MutuallyExclusive(Vec<Ident>),
+    /// `at_least_one_of(a, b, ...)`: at least one of the fields must be set
+    AtLeastOneOf(Vec<Ident>),
+    /// `required_if(condition, field)`: `field` must be set when `condition` is set
+    RequiredIf { condition: Ident, field: Ident },
+    /// `compare(a <= b)`: the value of field `a` must compare to the value of field `b`
+    Compare {
+        left: Ident,
+        op: BinOp,
+        right: Ident,
+    },
+    /// `rule = "self.minReplicas <= self.maxReplicas"`: an expression evaluated against the struct
+    Expression {
+        rule: LitStr,
+        message: Option<LitStr>,
+        code: Option<LitStr>,
+        warn: bool,
+    },
+    /// `message = "..."`: the message of the expression rule in the same attribute,
+    /// only present until the rules are extracted
+    Message(LitStr),
+    /// `code = "..."`: the code of the expression rule in the same attribute,
+    /// only present until the rules are extracted
+    Code(LitStr),
+    /// `warn`: violations of the expression rule in the same attribute are warnings,
+    /// only present until the rules are extracted
+    Warn(Ident),
+}
+
+impl Parse for StructRule {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.call(Ident::parse_any)?;
+        if name == "warn" && !input.peek(Token![=]) && !input.peek(syn::token::Paren) {
+            return Ok(Self::Warn(name));
+        }
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<LitStr>()?;
+            return match name.to_string().as_str() {
+                "rule" => Ok(Self::Expression {
+                    rule: value,
+                    message: None,
+                    code: None,
+                    warn: false,
+                }),
+                "message" => Ok(Self::Message(value)),
+                "code" => Ok(Self::Code(value)),
+                _ => Err(syn::Error::new(
+                    name.span(),
+                    format!(
+                        "Unknown struct validation rule `{name}`, expected `rule`, `message` or `code`{}",
+                        suggestion(&name.to_string(), ["rule", "message", "code"])
+                    ),
+                )),
+            };
+        }
+        if !input.peek(syn::token::Paren) {
+            return Err(unknown_rule(&name));
+        }
+        let content;
+        syn::parenthesized!(content in input);
+        match name.to_string().as_str() {
+            "mutually_exclusive" | "at_least_one_of" => {
+                let fields = content
+                    .parse_terminated(Ident::parse_any, Token![,])?
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                if fields.len() < 2 {
+                    return Err(syn::Error::new(
+                        name.span(),
+                        format!("`{name}` requires at least two fields"),
+                    ));
+                }
+                if name == "mutually_exclusive" {
+                    Ok(Self::MutuallyExclusive(fields))
+                } else {
+                    Ok(Self::AtLeastOneOf(fields))
+                }
+            }
+            "required_if" => {
+                let condition = content.call(Ident::parse_any)?;
+                content.parse::<Token![,]>()?;
+                let field = content.call(Ident::parse_any)?;
+                Ok(Self::RequiredIf { condition, field })
+            }
+            "compare" => {
+                let expr = content.parse::<Expr>()?;
+                let Expr::Binary(binary) = &expr else {
+                    return Err(syn::Error::new(
+                        expr.span(),
+                        "expected a comparison such as `min <= max`",
+                    ));
+                };
+                if !matches!(
+                    binary.op,
+                    BinOp::Lt(_)
+                        | BinOp::Le(_)
+                        | BinOp::Gt(_)
+                        | BinOp::Ge(_)
+                        | BinOp::Eq(_)
+                        | BinOp::Ne(_)
+                ) {
+                    return Err(syn::Error::new(
+                        binary.op.span(),
+                        "expected one of `<`, `<=`, `>`, `>=`, `==`, `!=`",
+                    ));
+                }
+                Ok(Self::Compare {
+                    left: expr_to_field(&binary.left)?,
+                    op: binary.op,
+                    right: expr_to_field(&binary.right)?,
+                })
+            }
+            _ => Err(unknown_rule(&name)),
+        }
+    }
+}
+
+fn unknown_rule(name: &Ident) -> syn::Error {
+    let message = match did_you_mean(&name.to_string(), STRUCT_RULES) {
+        Some(candidate) => {
+            format!("Unknown struct validation rule `{name}`, did you mean `{candidate}`?")
+        }
+        None => format!(
+            "Unknown struct validation rule `{name}`, expected one of: {}",
+            STRUCT_RULES.join(", ")
+        ),
+    };
+    syn::Error::new(name.span(), message)
+}
+
+fn expr_to_field(expr: &Expr) -> syn::Result<Ident> {
+    match expr {
+        Expr::Path(expr_path) if expr_path.path.get_ident().is_some() => {
+            Ok(expr_path.path.get_ident().unwrap().clone())
+        }
+        _ => Err(syn::Error::new(expr.span(), "expected a field name")),
+    }
+}
+
+/// Extract the struct level `#[cvalidate(...)]` rules.
+pub(crate) fn extract_struct_rules(attrs: &[syn::Attribute]) -> syn::Result<Vec<StructRule>> {
+    let mut rules = Vec::new();
+    for attr in attrs {
+        if attr.path().is_ident("cvalidate") {
+            let mut attr_rules = Vec::new();
+            let mut attr_message = None;
+            let mut attr_code = None;
+            let mut attr_warn = None;
+            for rule in
+                attr.parse_args_with(Punctuated::<StructRule, Token![,]>::parse_terminated)?
+            {
+                match rule {
+                    StructRule::Message(message) => attr_message = Some(message),
+                    StructRule::Code(code) => attr_code = Some(code),
+                    StructRule::Warn(warn) => attr_warn = Some(warn),
+                    rule => attr_rules.push(rule),
+                }
+            }
+            // `message`, `code` and `warn` describe the violation of the `rule` expression declared in the same attribute
+            let option = attr_message
+                .as_ref()
+                .map(|message| ("message", message.span()))
+                .or_else(|| attr_code.as_ref().map(|code| ("code", code.span())))
+                .or_else(|| attr_warn.as_ref().map(|warn| ("warn", warn.span())));
+            if let Some((name, span)) = option {
+                let Some(StructRule::Expression {
+                    message,
+                    code,
+                    warn,
+                    ..
+                }) = attr_rules
+                    .iter_mut()
+                    .find(|rule| matches!(rule, StructRule::Expression { .. }))
+                else {
+                    return Err(syn::Error::new(
+                        span,
+                        format!(
+                            "`{name}` requires a `rule` in the same `#[cvalidate(...)]` attribute"
+                        ),
+                    ));
+                };
+                *message = attr_message;
+                *code = attr_code;
+                *warn = attr_warn.is_some();
+            }
+            rules.extend(attr_rules);
+        }
+    }
+    Ok(rules)
+}
+
+/// Generate the validation code for the struct level rules.
+/// Every field referenced by a rule must exist in `fields`, errors are located at the referenced fields.
+pub(crate) fn generate_struct_validations(
+    rules: &[StructRule],
+    fields: &Fields,
+) -> syn::Result<TokenStream> {
+    let mut validations = Vec::new();
+    for rule in rules {
+        let validation = match rule {
+            StructRule::MutuallyExclusive(idents) => {
+                let names = check_fields(idents, fields)?;
+                quote! {
+                    {
+                        let set: Vec<&str> = [#((#names, ::choreo_api::IsSet::is_set(&self.#idents))),*]
+                            .into_iter()
+                            .filter_map(|(name, is_set)| is_set.then_some(name))
+                            .collect();
+                        if set.len() > 1 {
+                            for name in &set {
+                                let others = set
+                                    .iter()
+                                    .filter(|other| *other != name)
+                                    .map(|other| format!("'{}'", path.field(*other)))
+                                    .collect::<Vec<_>>()
+                                    .join(", ");
+                                errors.push(
+                                    ::choreo_api::ValidationError::new(path.field(*name), ::choreo_api::ValidationErrorKind::Conflict)
+                                        .with_constraint(others),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            StructRule::AtLeastOneOf(idents) => {
+                let names = check_fields(idents, fields)?;
+                quote! {
+                    if #(!::choreo_api::IsSet::is_set(&self.#idents))&&* {
+                        let names = [#(#names),*];
+                        for name in names {
+                            let others = names
+                                .iter()
+                                .filter(|other| **other != name)
+                                .map(|other| format!("'{}'", path.field(*other)))
+                                .collect::<Vec<_>>();
+                            let condition = match others.as_slice() {
+                                [other] => format!("{} is not set", other),
+                                others => format!("none of {} is set", others.join(", ")),
+                            };
+                            errors.push(
+                                ::choreo_api::ValidationError::new(path.field(name), ::choreo_api::ValidationErrorKind::Required)
+                                    .with_constraint(condition),
+                            );
+                        }
+                    }
+                }
+            }
+            StructRule::RequiredIf { condition, field } => {
+                let names = check_fields(&[condition.clone(), field.clone()], fields)?;
+                let (condition_name, field_name) = (&names[0], &names[1]);
+                quote! {
+                    if ::choreo_api::IsSet::is_set(&self.#condition) && !::choreo_api::IsSet::is_set(&self.#field) {
+                        errors.push(
+                            ::choreo_api::ValidationError::new(path.field(#field_name), ::choreo_api::ValidationErrorKind::Required)
+                                .with_constraint(format!("'{}' is set", path.field(#condition_name))),
+                        );
+                    }
+                }
+            }
+            StructRule::Compare { left, op, right } => {
+                let names = check_fields(&[left.clone(), right.clone()], fields)?;
+                let (left_name, right_name) = (&names[0], &names[1]);
+                let op_str = quote!(#op).to_string();
+                let left_value = operand(left, fields);
+                let right_value = operand(right, fields);
+                quote! {
+                    if let (Some(left), Some(right)) = (#left_value, #right_value) {
+                        if !(left #op right) {
+                            errors.push(
+                                ::choreo_api::ValidationError::new(path.field(#left_name), ::choreo_api::ValidationErrorKind::OutOfRange)
+                                    .with_constraint(format!("{} {}", #op_str, path.field(#right_name)))
+                                    .with_value(format!("{:?}", left)),
+                            );
+                        }
+                    }
+                }
+            }
+            StructRule::Expression {
+                rule,
+                message,
+                code,
+                warn,
+            } => {
+                let message = message.as_ref().map(|message| (message.value(), message.span()));
+                let code = code.as_ref().map(LitStr::value);
+                let expression_rule = generate_expression_rule(
+                    &rule.value(),
+                    message.as_ref().map(|(message, span)| (message.as_str(), *span)),
+                    code.as_deref(),
+                    *warn,
+                    rule.span(),
+                )?;
+                // Rules referring to `oldSelf` are only checked on updates
+                if is_transition(rule) {
+                    continue;
+                }
+                quote! {
+                    {
+                        #expression_rule
+                        rule.validate_at(self, path, errors);
+                    }
+                }
+            }
+            StructRule::Message(_) | StructRule::Code(_) | StructRule::Warn(_) => quote! {},
+        };
+        validations.push(validation);
+    }
+    Ok(quote! { #(#validations)* })
+}
+
+/// Generate the transition checks for the struct level expression rules that refer to `oldSelf`.
+pub(crate) fn generate_struct_transitions(rules: &[StructRule]) -> syn::Result<TokenStream> {
+    let mut transitions = Vec::new();
+    for rule in rules {
+        let StructRule::Expression {
+            rule,
+            message,
+            code,
+            warn,
+        } = rule
+        else {
+            continue;
+        };
+        if !is_transition(rule) {
+            continue;
+        }
+        let message = message.as_ref().map(|message| (message.value(), message.span()));
+        let code = code.as_ref().map(LitStr::value);
+        let expression_rule = generate_expression_rule(
+            &rule.value(),
+            message.as_ref().map(|(message, span)| (message.as_str(), *span)),
+            code.as_deref(),
+            *warn,
+            rule.span(),
+        )?;
+        transitions.push(quote! {
+            {
+                #expression_rule
+                rule.validate_transition_at(self, old, path, errors);
+            }
+        });
+    }
+    Ok(quote! { #(#transitions)* })
+}
+
+fn is_transition(rule: &LitStr) -> bool {
+    ::choreo_api::expr::Program::compile(&rule.value()).is_ok_and(|program| program.is_transition())
+}
+
+/// Check that all referenced fields exist, returns the names used in the field path.
+fn check_fields(idents: &[Ident], fields: &Fields) -> syn::Result<Vec<String>> {
+    idents
+        .iter()
+        .map(|ident| {
+            if find_field_type(ident, fields).is_none() {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("Unknown field `{ident}` in struct validation rule"),
+                ));
+            }
+            Ok(ident.unraw().to_string())
+        })
+        .collect()
+}
+
+fn find_field_type<'a>(ident: &Ident, fields: &'a Fields) -> Option<&'a Type> {
+    fields
+        .iter()
+        .find(|field| {
+            field
+                .ident
+                .as_ref()
+                .is_some_and(|field_ident| field_ident.unraw() == ident.unraw())
+        })
+        .map(|field| &field.ty)
+}
+
+/// Returns the comparison operand for the field as an `Option<&T>`, so optional fields
+/// are only compared when they are set.
+fn operand(ident: &Ident, fields: &Fields) -> TokenStream {
+    let is_option = find_field_type(ident, fields).is_some_and(|typ| {
+        matches!(typ, Type::Path(type_path) if type_path.path.segments.last().is_some_and(|segment| segment.ident == "Option"))
+    });
+    if is_option {
+        quote! { self.#ident.as_ref() }
+    } else {
+        quote! { Some(&self.#ident) }
+    }
+}
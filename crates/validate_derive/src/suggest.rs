@@ -0,0 +1,57 @@
+/// Returns the candidate closest to the unknown `name`, if any is close enough to be a likely typo
+/// or `name` abbreviates it, e.g. `minLength` for `minLen`.
+pub(crate) fn did_you_mean<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let normalized = normalize(name);
+    let max_distance = (normalized.len() / 3).max(1);
+    candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let normalized_candidate = normalize(candidate);
+            let distance = edit_distance(&normalized, &normalized_candidate);
+            let is_abbreviation =
+                normalized.len() >= 3 && normalized_candidate.starts_with(&normalized);
+            (distance <= max_distance || is_abbreviation).then(|| {
+                // On a tie prefer the spelling closest to the one written, e.g. `min_length` for `min_lenght`
+                let exact_distance = edit_distance(&chars(name), &chars(candidate));
+                (distance, exact_distance, candidate)
+            })
+        })
+        .min()
+        .map(|(_, _, candidate)| candidate)
+}
+
+/// Format the suggestion for the unknown `name` as the tail of an error message.
+pub(crate) fn suggestion<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> String {
+    did_you_mean(name, candidates)
+        .map(|candidate| format!(", did you mean `{candidate}`?"))
+        .unwrap_or_default()
+}
+
+/// Compare case insensitively and ignoring `_`, so that `min_len` is as close to `minLength` as `minLen` is.
+fn normalize(s: &str) -> Vec<char> {
+    s.chars()
+        .filter(|c| *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn chars(s: &str) -> Vec<char> {
+    s.chars().collect()
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
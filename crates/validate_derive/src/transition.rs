@@ -0,0 +1,278 @@
+use crate::rules::{
+    generate_error, generate_expression_rule, parse_list_map_keys, FieldTarget, ValidationRule,
+};
+use crate::validate::{
+    extract_inner_type_for_type, extract_validation_rules, field_recursion, is_type, Recursion,
+};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{DataEnum, Field, Fields, Ident, Type};
+
+/// Generate the transition checks for a field, comparing the field of `self` described by
+/// `field` with the same field of the previous version, `old` evaluates to that old field value.
+pub(crate) fn generate_transitions_for_field(
+    field: &Field,
+    target: &FieldTarget,
+    old: &TokenStream,
+) -> TokenStream {
+    // Malformed rules are reported by the validations generated for `validate_at`
+    let rules = extract_validation_rules(field).unwrap_or_default();
+    let recursion = field_recursion(&rules, target).unwrap_or(Recursion::Skip);
+    if recursion == Recursion::Skip {
+        return quote! {};
+    }
+    let is_option = is_type(&field.ty, "Option");
+    let rule_transitions = rules
+        .iter()
+        .map(|rule| generate_transition_for_rule(rule, target, old, is_option));
+    // List items are matched between the two versions by their `list_map_keys`
+    let list_map_keys = rules
+        .iter()
+        .find(|rule| rule.operator == "list_map_keys")
+        .and_then(|rule| parse_list_map_keys(rule).ok());
+    let nested_transitions =
+        generate_nested_transitions_for_field(field, target, old, list_map_keys.as_deref());
+    quote! {
+        #(#rule_transitions)*
+        #nested_transitions
+    }
+}
+
+/// Generate a match on the active variants of `self` and `old`, checking the transitions of
+/// the variant fields when both are the same variant. Switching the variant is always allowed.
+pub(crate) fn generate_transitions_for_enum(data_enum: &DataEnum) -> TokenStream {
+    let mut arms = Vec::new();
+    for variant in &data_enum.variants {
+        let variant_ident = &variant.ident;
+        let variant_name = variant_ident.to_string();
+
+        let mut bindings = Vec::new();
+        let mut old_bindings = Vec::new();
+        let mut transitions = Vec::new();
+        for (index, field) in variant.fields.iter().enumerate() {
+            let binding = format_ident!("__field{}", index);
+            let old_binding = format_ident!("__old{}", index);
+            let name = field
+                .ident
+                .as_ref()
+                .map_or_else(|| index.to_string(), |ident| ident.to_string());
+            let field_transitions = generate_transitions_for_field(
+                field,
+                &FieldTarget::bound(name, &binding),
+                &quote! { (*#old_binding) },
+            );
+            if field_transitions.is_empty() {
+                bindings.push(quote! { _ });
+                old_bindings.push(quote! { _ });
+            } else {
+                bindings.push(quote! { #binding });
+                old_bindings.push(quote! { #old_binding });
+                transitions.push(field_transitions);
+            }
+        }
+        if transitions.is_empty() {
+            continue;
+        }
+
+        let (pattern, old_pattern) = match &variant.fields {
+            Fields::Named(fields) => {
+                let field_idents = fields
+                    .named
+                    .iter()
+                    .map(|field| &field.ident)
+                    .collect::<Vec<_>>();
+                (
+                    quote! { Self::#variant_ident { #(#field_idents: #bindings),* } },
+                    quote! { Self::#variant_ident { #(#field_idents: #old_bindings),* } },
+                )
+            }
+            _ => (
+                quote! { Self::#variant_ident(#(#bindings),*) },
+                quote! { Self::#variant_ident(#(#old_bindings),*) },
+            ),
+        };
+        arms.push(quote! {
+            (#pattern, #old_pattern) => {
+                let path = path.field(#variant_name);
+                #(#transitions)*
+            }
+        });
+    }
+
+    if arms.is_empty() {
+        return quote! {};
+    }
+    // A single variant enum is matched exhaustively by its own arm
+    let other = if data_enum.variants.len() > 1 {
+        quote! { _ => {} }
+    } else {
+        quote! {}
+    };
+    quote! {
+        match (self, old) {
+            #(#arms)*
+            #other
+        }
+    }
+}
+
+/// Generate the check of a single transition marker, e.g. `immutable` or `increase_only`,
+/// or of an expression rule that refers to `oldSelf`. Other rules have no transition.
+fn generate_transition_for_rule(
+    rule: &ValidationRule,
+    field: &FieldTarget,
+    old: &TokenStream,
+    is_option: bool,
+) -> TokenStream {
+    let access = &field.access;
+    match rule.operator.as_str() {
+        "immutable" => {
+            let error = generate_error(rule, field, quote! { Immutable }, None, None);
+            quote! {
+                if #access != #old {
+                    errors.push(#error);
+                }
+            }
+        }
+        "immutable_once_set" => {
+            let error = generate_error(rule, field, quote! { Immutable }, Some("once set"), None);
+            quote! {
+                if ::choreo_api::IsSet::is_set(&#old) && #access != #old {
+                    errors.push(#error);
+                }
+            }
+        }
+        "increase_only" | "decrease_only" => {
+            let (invalid_op, constraint) = if rule.operator == "increase_only" {
+                (quote! { < }, "may only increase")
+            } else {
+                (quote! { > }, "may only decrease")
+            };
+            let error = generate_error(
+                rule,
+                field,
+                quote! { InvalidTransition },
+                Some(constraint),
+                Some(quote! { item }),
+            );
+            if is_option {
+                quote! {
+                    if let (Some(item), Some(old_item)) = (&#access, &#old) {
+                        if item #invalid_op old_item {
+                            errors.push(#error);
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    {
+                        let item = &#access;
+                        if item #invalid_op &#old {
+                            errors.push(#error);
+                        }
+                    }
+                }
+            }
+        }
+        "rule" => {
+            let Some(source) = &rule.value else {
+                return quote! {};
+            };
+            if !::choreo_api::expr::Program::compile(source)
+                .is_ok_and(|program| program.is_transition())
+            {
+                return quote! {};
+            }
+            let expression_rule = match generate_expression_rule(
+                source,
+                rule.message.as_deref().map(|message| (message, rule.message_span)),
+                rule.code.as_deref(),
+                rule.warn,
+                rule.span,
+            ) {
+                Ok(expression_rule) => expression_rule,
+                Err(err) => return err.to_compile_error(),
+            };
+            let field_path = &field.path;
+            // Like Kubernetes, transition rules on optional fields only apply when both versions are set
+            if is_option {
+                quote! {
+                    if let (Some(item), Some(old_item)) = (&#access, &#old) {
+                        #expression_rule
+                        rule.validate_transition_at(item, old_item, &#field_path, errors);
+                    }
+                }
+            } else {
+                quote! {
+                    {
+                        #expression_rule
+                        rule.validate_transition_at(&#access, &#old, &#field_path, errors);
+                    }
+                }
+            }
+        }
+        _ => quote! {},
+    }
+}
+
+/// Generate the transition check of a field through its `Validate` implementation.
+///
+/// The items of a list with `list_map_keys` are matched by their keys, other lists are not
+/// checked since their items can not be matched between the two versions.
+fn generate_nested_transitions_for_field(
+    field: &Field,
+    target: &FieldTarget,
+    old: &TokenStream,
+    list_map_keys: Option<&[Ident]>,
+) -> TokenStream {
+    let field_path = &target.path;
+    let access = &target.access;
+    let typ = &field.ty;
+    let Some(keys) = list_map_keys else {
+        let validate_transition_at = quote_spanned! { typ.span()=>
+            <#typ as ::choreo_api::Validate>::validate_transition_at
+        };
+        return quote! {
+            #validate_transition_at(&#access, &#old, &#field_path, errors);
+        };
+    };
+    // The items of the list are checked, the field type only locates a missing implementation
+    let validate_transition_at =
+        quote_spanned! { typ.span()=> ::choreo_api::Validate::validate_transition_at };
+    let keyed_transition = generate_keyed_transition(&field.ty, keys, &validate_transition_at);
+    quote! {
+        {
+            let path = #field_path;
+            let item = &#access;
+            let old_item = &#old;
+            #keyed_transition
+        }
+    }
+}
+
+/// Generate the transition check of the items of the list `item` against the items of
+/// `old_item` with the same `keys`, located at `path`.
+fn generate_keyed_transition(
+    typ: &Type,
+    keys: &[Ident],
+    validate_transition_at: &TokenStream,
+) -> TokenStream {
+    if is_type(typ, "Option") {
+        if let Some(inner_type) = extract_inner_type_for_type(typ, "Option") {
+            let inner_transition = generate_keyed_transition(&inner_type, keys, validate_transition_at);
+            return quote! {
+                if let (Some(item), Some(old_item)) = (item, old_item) {
+                    #inner_transition
+                }
+            };
+        }
+    }
+    quote! {
+        for (index, item) in item.iter().enumerate() {
+            if let Some(old_item) = old_item.iter().find(|old_item| #(old_item.#keys == item.#keys)&&*) {
+                #validate_transition_at(item, old_item, &path.index(index), errors);
+            }
+        }
+    }
+}
@@ -1,8 +1,15 @@
-use crate::rules::{RuleInfo, ValidationRule, RULE_REGISTRY};
+use crate::parse::{attribute_name, attribute_names, parse_rules, FIELD_OPTIONS};
+use crate::rules::{FieldTarget, RuleInfo, ValidationRule, RULE_REGISTRY};
+use crate::struct_rules::{
+    extract_struct_rules, generate_struct_transitions, generate_struct_validations,
+};
+use crate::suggest::suggestion;
+use crate::transition::{generate_transitions_for_enum, generate_transitions_for_field};
 use proc_macro2::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote, quote_spanned};
 use std::collections::HashSet;
-use syn::{DeriveInput, Field, Type, Data};
+use syn::spanned::Spanned;
+use syn::{Data, DataEnum, DeriveInput, Field, Fields, Type};
 
 pub(crate) fn derive(input: TokenStream) -> TokenStream {
     let derive_input: DeriveInput = match syn::parse2(input.clone()) {
@@ -23,152 +30,387 @@ pub(crate) fn derive(input: TokenStream) -> TokenStream {
     }
 
     let struct_name = &derive_input.ident;
+    // Fields of a type parameter are validated through their own rules, so they have to implement `Validate`
+    let mut generics = derive_input.generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(syn::parse_quote!(::choreo_api::Validate));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Struct level attributes declare cross-field rules, e.g. #[cvalidate(mutually_exclusive(a, b))]
+    let struct_rules = match extract_struct_rules(&derive_input.attrs) {
+        Ok(rules) => rules,
+        Err(err) => return err.to_compile_error(),
+    };
 
     let validations = match &derive_input.data {
-        syn::Data::Struct(data_struct) => data_struct
-            .fields
-            .iter()
-            .map(|field| generate_validations_for_field(field))
-            .collect::<Vec<_>>(),
+        syn::Data::Struct(data_struct) => {
+            let mut validations = struct_field_targets(&data_struct.fields)
+                .map(|(field, target, _)| generate_validations_for_field(field, &target))
+                .collect::<Vec<_>>();
+            match generate_struct_validations(&struct_rules, &data_struct.fields) {
+                Ok(struct_validations) => validations.push(struct_validations),
+                Err(err) => return err.to_compile_error(),
+            }
+            validations
+        }
+        syn::Data::Enum(data_enum) => {
+            if let Some(attr) = derive_input.attrs.iter().find(|attr| attr.path().is_ident("cvalidate")) {
+                return syn::Error::new_spanned(attr, "Struct level validation rules are not supported on enums")
+                    .to_compile_error();
+            }
+            vec![generate_validations_for_enum(data_enum)]
+        }
         _ => vec![],
     };
 
-    // this is the expanded code the compiler adds when the ChoreoDefault derive is added to a struct
+    // transitions compare the fields of self with the fields of the previous version old
+    let transitions = match &derive_input.data {
+        syn::Data::Struct(data_struct) => {
+            let mut transitions = struct_field_targets(&data_struct.fields)
+                .map(|(field, target, old)| generate_transitions_for_field(field, &target, &old))
+                .collect::<Vec<_>>();
+            match generate_struct_transitions(&struct_rules) {
+                Ok(struct_transitions) => transitions.push(struct_transitions),
+                Err(err) => return err.to_compile_error(),
+            }
+            transitions
+        }
+        syn::Data::Enum(data_enum) => vec![generate_transitions_for_enum(data_enum)],
+        _ => vec![],
+    };
+    // without transitions the default of the trait, which checks nothing, is used
+    let validate_transition_at = if transitions.iter().all(TokenStream::is_empty) {
+        quote! {}
+    } else {
+        quote! {
+            fn validate_transition_at(&self, old: &Self, path: &::choreo_api::FieldPath, errors: &mut ::choreo_api::ValidationErrors) {
+                #(#transitions)*
+            }
+        }
+    };
+
+    // this is the expanded code the compiler adds when the ChoreoValidate derive is added to a struct
     let expanded = quote! {
-        impl ::choreo_api::Validate for #struct_name {
-            fn validate(&self) -> Result<(), String> {
-                // errors collect the runtime validation errors.
-                let mut errors: Vec<String> = Vec::new();
+        impl #impl_generics ::choreo_api::Validate for #struct_name #ty_generics #where_clause {
+            fn validate_at(&self, path: &::choreo_api::FieldPath, errors: &mut ::choreo_api::ValidationErrors) {
+                // errors collect the runtime validation errors, located relative to path.
                 #(#validations)*
-                if errors.is_empty() {
-                    Ok(())
-                } else {
-                    Err(errors.join("\n"))
-                }
             }
+
+            #validate_transition_at
         }
     };
 
-    //eprintln!("Generated validation code {}", quote! { #expanded });
-
-    TokenStream::from(expanded)
-}
-
-fn generate_validations_for_field(field: &Field) -> TokenStream {
-    let field_name = field.ident.as_ref().expect("Expected named field");
-    let field_type = &field.ty;
-    match extract_validation_rules(field) {
-        // Case 1: Rules successfully extracted
-        Ok(rules) if !rules.is_empty() => {
-            let field_validations: Vec<TokenStream> = rules
-                .iter()
-                .map(|rule| {
-
-                    let operator = &rule.operator;
-                    //eprintln!("Processing rule for field `{}`: operator = {}, value = {:?}", field_name, operator, rule.value);
-                    if let Some(rule_info) = RULE_REGISTRY.get(rule.operator.as_str()) {
-                        let (is_valid, field_type_str, is_option) =
-                            is_type_valid(&field.ty, rule_info);
-
-                        if !is_valid {
-                            // Invalid rule for the field
-                            return quote! {
-                                compile_error!(concat!(
-                                    "Unsupported or invalid `#[",
-                                    stringify!(#operator),
-                                    "]` attribute for field `",
-                                    stringify!(#field_name),
-                                    "`."
-                                ));
-                            };
-                        }
-
-                        // Call the handler to generate validation code
-                        let c = (rule_info.handler)(rule, field_name, field_type_str, is_option);
-                        //eprintln!("processing rule handler \n{}", c);
-                        c
-                    } else {
-                        // Unknown rule
-                        quote! {
-                            compile_error!(concat!(
-                                "Unknown validation rule: '",
-                                stringify!(#operator),
-                                "' for field `",
-                                stringify!(#field_name),
-                                "`."
-                            ));
-                        }
-                    }
-                })
-                .collect();
+    expanded
+}
 
-            let c = quote! { #(#field_validations)* };
-            //eprintln!("expanded code \n{}", c);
-            c
+/// Returns the fields of a struct with their target and the expression accessing the same field
+/// of the previous version `old`. Named fields are accessed by name, tuple fields by index.
+fn struct_field_targets(fields: &Fields) -> impl Iterator<Item = (&Field, FieldTarget, TokenStream)> {
+    let is_newtype = matches!(fields, Fields::Unnamed(fields) if fields.unnamed.len() == 1);
+    fields.iter().enumerate().map(move |(index, field)| match &field.ident {
+        Some(ident) => (field, FieldTarget::named(ident), quote! { old.#ident }),
+        None => {
+            let member = syn::Index::from(index);
+            (field, FieldTarget::unnamed(index, is_newtype), quote! { old.#member })
         }
+    })
+}
+
+/// Generate a match on the active variant that validates the fields of that variant.
+/// Errors are located below the variant name, e.g. `source.Git.url`.
+fn generate_validations_for_enum(data_enum: &DataEnum) -> TokenStream {
+    let arms = data_enum.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let variant_name = variant_ident.to_string();
 
-        // Case 2: Errors during rule extraction
-        Err(err) => {
-            quote! {
-                compile_error!(#err);
+        let mut bindings = Vec::new();
+        let mut validations = Vec::new();
+        for (index, field) in variant.fields.iter().enumerate() {
+            let binding = format_ident!("__field{}", index);
+            let name = field
+                .ident
+                .as_ref()
+                .map_or_else(|| index.to_string(), |ident| ident.to_string());
+            let field_validations =
+                generate_validations_for_field(field, &FieldTarget::bound(name, &binding));
+            if field_validations.is_empty() {
+                bindings.push(quote! { _ });
+            } else {
+                bindings.push(quote! { #binding });
+                validations.push(field_validations);
             }
         }
 
-        // Case 3: No rules provided for the field
-        _ => {
-            // No validation needed for this field
-            if is_nested_struct(field_type) {
-                if field_name == "metadata" {
-                   return  quote! {} // Skip the `metadata` field
-                } else {
-                    return quote! {
-                        if let Err(e) = &self.#field_name.validate() {
-                            errors.push(format!(
-                                "Field '{}' failed validation '{}'",
-                                stringify!(#field_name),
-                                e
-                            ));
-                        }
-                    }
-                }
+        let pattern = match &variant.fields {
+            Fields::Named(fields) => {
+                let field_idents = fields.named.iter().map(|field| &field.ident);
+                quote! { Self::#variant_ident { #(#field_idents: #bindings),* } }
+            }
+            Fields::Unnamed(_) => quote! { Self::#variant_ident(#(#bindings),*) },
+            Fields::Unit => quote! { Self::#variant_ident },
+        };
+
+        if validations.is_empty() {
+            return quote! { #pattern => {} };
+        }
+        quote! {
+            #pattern => {
+                let path = path.field(#variant_name);
+                #(#validations)*
+            }
+        }
+    });
+
+    quote! {
+        match self {
+            #(#arms)*
+        }
+    }
+}
+
+fn generate_validations_for_field(field: &Field, target: &FieldTarget) -> TokenStream {
+    let rules = match extract_validation_rules(field) {
+        Ok(rules) => rules,
+        Err(err) => return err.to_compile_error(),
+    };
+    let recursion = match field_recursion(&rules, target) {
+        Ok(recursion) => recursion,
+        Err(err) => return err.to_compile_error(),
+    };
+    if recursion == Recursion::Skip {
+        return quote! {};
+    }
+
+    let rule_validations = rules
+        .iter()
+        .filter(|rule| !FIELD_OPTIONS.contains(&rule.operator.as_str()))
+        .map(|rule| generate_rule_validation(rule, &field.ty, target));
+    let nested_validation = generate_nested_validation_for_field(field, target);
+    quote! {
+        #(#rule_validations)*
+        #nested_validation
+    }
+}
+
+/// How the derive treats the value of a field besides its rules.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Recursion {
+    /// The field is also validated through its `Validate` implementation, the default.
+    /// `#[cvalidate(dive)]` states it explicitly.
+    Dive,
+    /// `#[cvalidate(skip)]`: the field is not validated at all
+    Skip,
+}
+
+/// Determine the recursion of a field from its `dive` and `skip` entries.
+pub(crate) fn field_recursion(rules: &[ValidationRule], target: &FieldTarget) -> syn::Result<Recursion> {
+    let mut recursion = Recursion::Dive;
+    let options: Vec<_> = rules
+        .iter()
+        .filter(|rule| FIELD_OPTIONS.contains(&rule.operator.as_str()))
+        .collect();
+    for rule in &options {
+        if rule.value.is_some() || !rule.args.is_empty() {
+            return Err(syn::Error::new(rule.span, format!("`{}` does not take a value", rule.operator)));
+        }
+        let first = options[0];
+        if rule.operator != first.operator {
+            return Err(syn::Error::new(
+                rule.name_span,
+                format!(
+                    "`{}` contradicts `{}` on field `{}`, use only one of them",
+                    rule.operator, first.operator, target.name
+                ),
+            ));
+        }
+        recursion = if rule.operator == "dive" { Recursion::Dive } else { Recursion::Skip };
+    }
+    if recursion == Recursion::Skip {
+        if let Some(rule) = rules.iter().find(|rule| rule.operator != "skip") {
+            return Err(syn::Error::new(
+                rule.name_span,
+                format!(
+                    "`skip` excludes field `{}` from validation, it can not be combined with `{}`",
+                    target.name,
+                    attribute_name(&rule.operator)
+                ),
+            ));
+        }
+    }
+    Ok(recursion)
+}
+
+/// Generate the validation of a single rule for the value of type `typ` described by `target`.
+fn generate_rule_validation(rule: &ValidationRule, typ: &Type, target: &FieldTarget) -> TokenStream {
+    let field_name = &target.name;
+    let operator = &rule.operator;
+    if matches!(operator.as_str(), "items" | "keys" | "values") {
+        return generate_collection_rule_validation(rule, typ, target);
+    }
+    let name = attribute_name(operator);
+    if FIELD_OPTIONS.contains(&operator.as_str()) {
+        // `dive` and `skip` are only handled for the field itself, e.g. not inside `items(...)`
+        return syn::Error::new(
+            rule.name_span,
+            format!("`{name}` applies to the whole field `{field_name}`, e.g. `#[cvalidate({name})]`"),
+        )
+        .to_compile_error();
+    }
+    // `one_of` on a list checks every item against the item type, like `items(one_of = [...])`
+    if operator == "one_of" && is_type(&extract_type_and_option_status(typ).0, "Vec") {
+        let items = ValidationRule {
+            operator: "items".to_string(),
+            sub_rules: vec![rule.clone()],
+            ..rule.clone()
+        };
+        return generate_collection_rule_validation(&items, typ, target);
+    }
+    let Some(rule_info) = RULE_REGISTRY.get(rule.operator.as_str()) else {
+        // Unknown rule
+        return syn::Error::new(
+            rule.name_span,
+            format!(
+                "Unknown validation rule `{name}` for field `{field_name}`{}",
+                suggestion(name, attribute_names())
+            ),
+        )
+        .to_compile_error();
+    };
+
+    let (is_valid, field_type_str, is_option) = is_type_valid(typ, rule_info);
+    if !is_valid {
+        // Invalid rule for the field
+        let typ = quote!(#typ).to_string().replace(' ', "");
+        let message = if rule_info.option_only {
+            format!("`{name}` requires an `Option` field, found field `{field_name}` of type `{typ}`")
+        } else {
+            let mut supported_types = rule_info.supported_types.iter().copied().collect::<Vec<_>>();
+            supported_types.sort_unstable();
+            format!(
+                "`{name}` does not support field `{field_name}` of type `{typ}`, supported types: {}",
+                supported_types.join(", ")
+            )
+        };
+        return syn::Error::new(rule.name_span, message).to_compile_error();
+    }
+
+    // Call the handler to generate validation code
+    (rule_info.handler)(rule, target, field_type_str, is_option)
+}
+
+/// Generate the validation of the sub rules of `items(...)`, `keys(...)` or `values(...)`
+/// for every element of a list or map. Errors are located at the index or key of the element,
+/// e.g. `args[2]` or `labels["tier"]`.
+fn generate_collection_rule_validation(
+    rule: &ValidationRule,
+    typ: &Type,
+    target: &FieldTarget,
+) -> TokenStream {
+    let (collection_type, is_option) = extract_type_and_option_status(typ);
+    let element_type = match rule.operator.as_str() {
+        "items" => extract_inner_type_for_type(&collection_type, "Vec"),
+        "keys" => extract_key_value_types_for_map(&collection_type).map(|(key, _)| key),
+        _ => extract_key_value_types_for_map(&collection_type).map(|(_, value)| value),
+    };
+    let Some(element_type) = element_type else {
+        let expected = if rule.operator == "items" { "a `Vec`" } else { "a `HashMap` or `BTreeMap`" };
+        return syn::Error::new(
+            rule.span,
+            format!("`{}(...)` requires {} field, found field `{}`", rule.operator, expected, target.name),
+        )
+        .to_compile_error();
+    };
+
+    let binding = format_ident!("__{}", rule.operator.trim_end_matches('s'));
+    let element = FieldTarget::element(target.name.clone(), &binding);
+    let element_validations = rule
+        .sub_rules
+        .iter()
+        .map(|sub_rule| generate_rule_validation(sub_rule, &element_type, &element));
+
+    let field_path = &target.path;
+    let access = &target.access;
+    let iteration = match rule.operator.as_str() {
+        "items" => quote! {
+            for (index, #binding) in collection.iter().enumerate() {
+                let path = #field_path.index(index);
+                #(#element_validations)*
+            }
+        },
+        "keys" => quote! {
+            for #binding in collection.keys() {
+                let path = #field_path.key(#binding);
+                #(#element_validations)*
+            }
+        },
+        _ => quote! {
+            for (key, #binding) in collection.iter() {
+                let path = #field_path.key(key);
+                #(#element_validations)*
+            }
+        },
+    };
+    if is_option {
+        quote! {
+            if let Some(ref collection) = #access {
+                #iteration
             }
-            quote! {}
         }
+    } else {
+        quote! {
+            {
+                let collection = &#access;
+                #iteration
+            }
+        }
+    }
+}
+
+/// Generate the validation of a field through its `Validate` implementation, `choreo_api`
+/// implements it for `Option`, `Vec`, maps, `Box`, primitives and `chrono::DateTime`.
+/// A missing implementation is reported at the field type, `#[cvalidate(skip)]` opts out.
+fn generate_nested_validation_for_field(field: &Field, target: &FieldTarget) -> TokenStream {
+    let field_path = &target.path;
+    let access = &target.access;
+    let typ = &field.ty;
+    let validate_at = quote_spanned! { typ.span()=> <#typ as ::choreo_api::Validate>::validate_at };
+    quote! {
+        #validate_at(&#access, &#field_path, errors);
     }
 }
 
+/// Rules that may be declared more than once on a field, like the list of `x-kubernetes-validations`.
+const REPEATABLE_RULES: [&str; 2] = ["rule", "custom"];
+
 /// Extract the `#[cvalidate(...)]` attributes from the field.
 ///
-/// Returns `Ok(Vec<ValidationRule>)` if parsing succeeds, or `Err(String)` if duplicates or invalid rules are found.
-fn extract_validation_rules(field: &syn::Field) -> Result<Vec<ValidationRule>, String> {
+/// Returns `Ok(Vec<ValidationRule>)` if parsing succeeds, or a spanned error if duplicates or malformed rules are found.
+pub(crate) fn extract_validation_rules(field: &syn::Field) -> syn::Result<Vec<ValidationRule>> {
     let mut parsed_rules = Vec::new();
     let mut seen_rules = HashSet::new(); // To track duplicate operators
 
     for attr in &field.attrs {
         if attr.path().is_ident("cvalidate") {
-            if let Ok(value) = attr.parse_args::<syn::LitStr>() {
-                let rules = value.value();
-
-                for rule in rules.split(',').map(|r| r.trim()) {
-                    let parts: Vec<&str> = rule.split('=').collect();
-                    let operator = parts[0].trim().to_string();
-                    let value = parts.get(1).map(|&v| v.trim().to_string());
-
-                    // Check for duplicates
-                    if !seen_rules.insert(operator.clone()) {
-                        let field_name = field
-                            .ident
-                            .as_ref()
-                            .map_or("<unknown>".to_string(), |id| id.to_string());
-                        return Err(format!(
+            for rule in parse_rules(attr)? {
+                // Check for duplicates, a field can carry several expression and custom rules,
+                // each in its own attribute with its own message
+                if !REPEATABLE_RULES.contains(&rule.operator.as_str()) && !seen_rules.insert(rule.operator.clone()) {
+                    let field_name = field
+                        .ident
+                        .as_ref()
+                        .map_or("<unknown>".to_string(), |id| id.to_string());
+                    return Err(syn::Error::new(
+                        rule.span,
+                        format!(
                             "Duplicate validation rule `{}` found for field `{}`.",
-                            operator,
-                            &field_name // Pass a reference to `field_name`
-                        ));
-                    }
-
-                    parsed_rules.push(ValidationRule { operator, value });
+                            rule.operator, field_name
+                        ),
+                    ));
                 }
+
+                parsed_rules.push(rule);
             }
         }
     }
@@ -204,7 +446,7 @@ fn is_type_valid(field_type: &Type, rule_info: &RuleInfo) -> (bool, Option<Strin
 /// - `is_option`: `true` if the outer type is an `Option`, otherwise `false`.
 fn extract_type_and_option_status(field_type: &Type) -> (Type, bool) {
     if let syn::Type::Path(type_path) = field_type {
-        if let Some(segment) = type_path.path.segments.first() {
+        if let Some(segment) = type_path.path.segments.last() {
             if segment.ident == "Option" {
                 if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
                     if let Some(syn::GenericArgument::Type(inner_type)) = args.args.first() {
@@ -218,7 +460,7 @@ fn extract_type_and_option_status(field_type: &Type) -> (Type, bool) {
 }
 
 // Helper to get the type name
-fn get_type_name(field_type: &Type) -> Option<String> {
+pub(crate) fn get_type_name(field_type: &Type) -> Option<String> {
     if let syn::Type::Path(type_path) = field_type {
         if let Some(segment) = type_path.path.segments.last() {
             return Some(segment.ident.to_string());
@@ -228,26 +470,46 @@ fn get_type_name(field_type: &Type) -> Option<String> {
 }
 
 
-fn is_nested_struct(typ: &syn::Type) -> bool {
-    if let Some(type_name) = get_type_name(typ) {
-        return !matches!(
-            type_name.as_str(),
-            "i8" | "i16"
-                | "i32"
-                | "i64"
-                | "u8"
-                | "u16"
-                | "u32"
-                | "u64"
-                | "f32"
-                | "f64"
-                | "bool"
-                | "String"
-                | "Option"
-                | "Vec"
-                | "HashMap"
-                | "BTreeMap"
-        );
+pub(crate) fn is_type(field_type: &Type, typ: &str) -> bool {
+    if let Type::Path(type_path) = field_type {
+        if let Some(segment) = type_path.path.segments.last() {
+            return segment.ident == typ;
+        }
     }
     false
-}
\ No newline at end of file
+}
+
+pub(crate) fn extract_inner_type_for_type(field_type: &Type, ident: &str) -> Option<Type> {
+    if let Type::Path(type_path) = field_type {
+        if let Some(segment) = type_path.path.segments.last() {
+            if segment.ident == ident {
+                if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                    if let Some(syn::GenericArgument::Type(inner_type)) = args.args.first() {
+                        return Some(inner_type.clone());
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
+pub(crate) fn extract_key_value_types_for_map(field_type: &Type) -> Option<(Type, Type)> {
+    if let Type::Path(type_path) = field_type {
+        if let Some(segment) = type_path.path.segments.last() {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                let mut types = args.args.iter().filter_map(|arg| {
+                    if let syn::GenericArgument::Type(typ) = arg {
+                        Some(typ.clone())
+                    } else {
+                        None
+                    }
+                });
+                if let (Some(key_type), Some(value_type)) = (types.next(), types.next()) {
+                    return Some((key_type, value_type));
+                }
+            }
+        }
+    }
+    None
+}
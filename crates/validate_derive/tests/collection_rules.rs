@@ -0,0 +1,68 @@
+use choreo_api::Validate;
+use std::collections::{BTreeMap, HashMap};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate, Default)]
+struct Metadata {
+    #[cvalidate(
+        max_items = 3,
+        keys(pattern = r"^([a-z0-9.-]+/)?[A-Za-z0-9_.-]{1,63}$"),
+        values(max_length = 5)
+    )]
+    labels: BTreeMap<String, String>,
+    #[cvalidate(items(format = "dns1123_label", max_length = 8))]
+    finalizers: Vec<String>,
+    #[cvalidate(items(ge = 1, le = 65535))]
+    ports: Option<Vec<u32>>,
+    #[cvalidate(values(min_length = 1))]
+    annotations: HashMap<String, String>,
+}
+
+#[test]
+fn valid_elements_pass() {
+    let metadata = Metadata {
+        labels: BTreeMap::from([("app.kubernetes.io/name".to_string(), "web".to_string())]),
+        finalizers: vec!["cleanup".to_string()],
+        ports: Some(vec![80, 443]),
+        annotations: HashMap::from([("note".to_string(), "x".to_string())]),
+    };
+    assert!(metadata.validate().is_ok());
+    assert!(Metadata::default().validate().is_ok());
+}
+
+#[test]
+fn failing_elements_are_located_at_their_index_or_key() {
+    let metadata = Metadata {
+        labels: BTreeMap::from([
+            ("bad key".to_string(), "ok".to_string()),
+            ("tier".to_string(), "backend".to_string()),
+        ]),
+        finalizers: vec!["ok".to_string(), "Not-A-Label".to_string()],
+        ports: Some(vec![0, 80]),
+        annotations: HashMap::from([("empty".to_string(), String::new())]),
+    };
+    let errors = metadata.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        [
+            r#"labels["bad key"]"#,
+            r#"labels["tier"]"#,
+            "finalizers[1]",
+            "finalizers[1]",
+            "ports[0]",
+            r#"annotations["empty"]"#,
+        ]
+    );
+}
+
+#[test]
+fn container_rules_still_apply() {
+    let metadata = Metadata {
+        labels: (0..4).map(|i| (format!("k{i}"), "v".to_string())).collect(),
+        ..Default::default()
+    };
+    assert_eq!(paths(&metadata.validate().unwrap_err()), ["labels"]);
+}
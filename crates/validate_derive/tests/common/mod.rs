@@ -0,0 +1,7 @@
+//! Helpers shared by the integration tests, included with `mod common;`
+use choreo_api::ValidationErrors;
+
+/// The paths of the errors as they are displayed, in the order they were recorded
+pub fn paths(errors: &ValidationErrors) -> Vec<String> {
+    errors.paths().map(ToString::to_string).collect()
+}
@@ -0,0 +1,6 @@
+/// Invalid `#[cvalidate(...)]` attributes are reported at the offending rule, see the `.stderr` next to each case.
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
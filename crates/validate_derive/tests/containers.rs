@@ -0,0 +1,60 @@
+use choreo_api::Validate;
+use std::collections::{BTreeMap, HashMap};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate, Clone)]
+struct PortSpec {
+    #[cvalidate(min_length = 1)]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct ServiceSpec {
+    ports: Option<Vec<PortSpec>>,
+    named: Option<BTreeMap<String, PortSpec>>,
+    groups: Vec<Vec<PortSpec>>,
+    by_zone: HashMap<String, Vec<PortSpec>>,
+}
+
+#[test]
+fn items_of_nested_containers_are_validated() {
+    let empty = PortSpec {
+        name: String::new(),
+    };
+    let http = PortSpec {
+        name: "http".to_string(),
+    };
+    let spec = ServiceSpec {
+        ports: Some(vec![http.clone(), empty.clone()]),
+        named: Some(BTreeMap::from([
+            ("a".to_string(), http.clone()),
+            ("b".to_string(), empty.clone()),
+        ])),
+        groups: vec![vec![], vec![http.clone(), empty.clone()]],
+        by_zone: HashMap::from([("eu".to_string(), vec![empty])]),
+    };
+    let errors = spec.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        [
+            "ports[1].name",
+            r#"named["b"].name"#,
+            "groups[1][1].name",
+            r#"by_zone["eu"][0].name"#,
+        ]
+    );
+}
+
+#[test]
+fn missing_containers_are_valid() {
+    let spec = ServiceSpec {
+        ports: None,
+        named: None,
+        groups: Vec::new(),
+        by_zone: HashMap::new(),
+    };
+    assert!(spec.validate().is_ok());
+}
@@ -0,0 +1,91 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+/// Rules shared between crates, referenced by path in `custom = ...`
+mod rules {
+    use choreo_api::Rule;
+
+    pub struct Semver;
+
+    impl Rule<String> for Semver {
+        fn name(&self) -> &str {
+            "semver"
+        }
+
+        fn check(&self, value: &String) -> Result<(), String> {
+            let parts = value.split('.').collect::<Vec<_>>();
+            if parts.len() == 3 && parts.iter().all(|part| part.parse::<u64>().is_ok()) {
+                Ok(())
+            } else {
+                Err(format!("'{value}' is not a semantic version"))
+            }
+        }
+    }
+
+    pub struct AtMost(pub u64);
+
+    impl AtMost {
+        pub fn gibibytes(limit: u64) -> Self {
+            Self(limit)
+        }
+    }
+
+    impl Rule<u64> for AtMost {
+        fn name(&self) -> &str {
+            "at_most"
+        }
+
+        fn check(&self, value: &u64) -> Result<(), String> {
+            if *value <= self.0 {
+                Ok(())
+            } else {
+                Err(format!("{value}Gi exceeds {}Gi", self.0))
+            }
+        }
+    }
+}
+
+#[derive(ChoreoValidate)]
+struct Chart {
+    #[cvalidate(custom = rules::Semver)]
+    version: String,
+    #[cvalidate(custom = rules::Semver)]
+    app_version: Option<String>,
+    #[cvalidate(items(custom = rules::Semver))]
+    compatible: Vec<String>,
+    #[cvalidate(custom = rules::AtMost::gibibytes(10))]
+    storage: u64,
+}
+
+#[test]
+fn rules_from_another_module_are_applied() {
+    let chart = Chart {
+        version: "1.2.3".to_string(),
+        app_version: None,
+        compatible: vec!["1.0.0".to_string()],
+        storage: 10,
+    };
+    assert!(chart.validate().is_ok());
+
+    let chart = Chart {
+        version: "1.2".to_string(),
+        app_version: Some("v1".to_string()),
+        compatible: vec!["1.0.0".to_string(), "latest".to_string()],
+        storage: 11,
+    };
+    let errors = chart.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        ["version", "app_version", "compatible[1]", "storage"]
+    );
+    assert!(errors
+        .iter()
+        .all(|error| error.kind == ValidationErrorKind::Custom));
+    assert_eq!(
+        errors.iter().last().unwrap().to_string(),
+        "Field 'storage' failed custom validation 'at_most': 11Gi exceeds 10Gi"
+    );
+}
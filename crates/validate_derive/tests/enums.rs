@@ -0,0 +1,56 @@
+use choreo_api::Validate;
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate)]
+enum Source {
+    Git {
+        #[cvalidate(format = "uri")]
+        url: String,
+        #[cvalidate(min_length = 1)]
+        branch: String,
+    },
+    Oci(#[cvalidate(min_length = 1)] String),
+    Empty,
+}
+
+#[derive(ChoreoValidate)]
+struct Spec {
+    source: Source,
+    mirrors: Vec<Source>,
+}
+
+#[test]
+fn active_variant_is_validated() {
+    let source = Source::Git {
+        url: "not a uri".to_string(),
+        branch: String::new(),
+    };
+    assert_eq!(
+        paths(&source.validate().unwrap_err()),
+        ["Git.url", "Git.branch"]
+    );
+    assert_eq!(
+        paths(&Source::Oci(String::new()).validate().unwrap_err()),
+        ["Oci.0"]
+    );
+    assert!(Source::Oci("nginx".to_string()).validate().is_ok());
+    assert!(Source::Empty.validate().is_ok());
+}
+
+#[test]
+fn variant_is_named_in_nested_paths() {
+    let spec = Spec {
+        source: Source::Git {
+            url: "https://example.com/repo.git".to_string(),
+            branch: String::new(),
+        },
+        mirrors: vec![Source::Empty, Source::Oci(String::new())],
+    };
+    assert_eq!(
+        paths(&spec.validate().unwrap_err()),
+        ["source.Git.branch", "mirrors[1].Oci.0"]
+    );
+}
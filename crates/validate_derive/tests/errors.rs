@@ -0,0 +1,118 @@
+use choreo_api::{FieldPath, Validate, ValidationError, ValidationErrorKind, ValidationErrors};
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Container {
+    #[cvalidate(min_length = 3, max_length = 8)]
+    name: String,
+    #[cvalidate(pattern = "^[a-z]+$")]
+    image: String,
+    #[cvalidate(ge = 1, le = 10)]
+    replicas: u32,
+    #[cvalidate(required)]
+    command: Option<String>,
+}
+
+#[test]
+fn valid_object_has_no_errors() {
+    let container = Container {
+        name: "web".to_string(),
+        image: "nginx".to_string(),
+        replicas: 2,
+        command: Some("run".to_string()),
+    };
+    assert_eq!(container.validate(), Ok(()));
+}
+
+#[test]
+fn every_violation_is_reported() {
+    let container = Container {
+        name: "a".to_string(),
+        image: "Nginx".to_string(),
+        replicas: 11,
+        command: None,
+    };
+    let errors = container.validate().unwrap_err();
+    let kinds = errors.iter().map(|error| error.kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ValidationErrorKind::TooShort,
+            ValidationErrorKind::PatternMismatch,
+            ValidationErrorKind::OutOfRange,
+            ValidationErrorKind::Required,
+        ]
+    );
+
+    let name = errors.iter().next().unwrap();
+    assert_eq!(name.path, FieldPath::root().field("name"));
+    assert_eq!(name.constraint.as_deref(), Some(">= 3"));
+    assert_eq!(name.value.as_deref(), Some("1"));
+    assert_eq!(name.code(), "too_short");
+    assert_eq!(
+        errors.to_string(),
+        "Field 'name' length must be >= 3.\n\
+         Field 'image' does not match the required pattern: '^[a-z]+$'.\n\
+         Field 'replicas' must be <= 10.\n\
+         Field 'command' is required"
+    );
+}
+
+#[derive(Debug)]
+enum ImageError {
+    MissingTag,
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("the image needs a tag")
+    }
+}
+
+#[derive(ChoreoValidate)]
+struct Image {
+    #[cvalidate(fn = check_reference)]
+    reference: String,
+}
+
+impl Image {
+    fn check_reference(&self) -> Result<(), ImageError> {
+        match self.reference.contains(':') {
+            true => Ok(()),
+            false => Err(ImageError::MissingTag),
+        }
+    }
+}
+
+#[test]
+fn custom_function_errors_only_need_display() {
+    let image = Image {
+        reference: "nginx:1.27".to_string(),
+    };
+    assert!(image.validate().is_ok());
+
+    let image = Image {
+        reference: "nginx".to_string(),
+    };
+    assert_eq!(
+        image.validate().unwrap_err().to_string(),
+        "Field 'reference' failed custom validation 'check_reference': the image needs a tag"
+    );
+}
+
+#[test]
+fn errors_can_be_built_and_collected() {
+    let mut errors = ValidationErrors::new();
+    assert!(errors.is_empty());
+    errors.push(
+        ValidationError::new(FieldPath::root().field("spec"), ValidationErrorKind::Custom)
+            .with_constraint("check")
+            .with_message("broken"),
+    );
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors.to_string(),
+        "Field 'spec' failed custom validation 'check': broken"
+    );
+    assert!(errors.clone().into_result().is_err());
+}
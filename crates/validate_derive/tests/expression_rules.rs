@@ -0,0 +1,80 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use serde::Serialize;
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cvalidate(
+    rule = "self.minReplicas <= self.maxReplicas",
+    message = "minReplicas must not exceed maxReplicas"
+)]
+struct Scaling {
+    min_replicas: u32,
+    max_replicas: u32,
+    #[cvalidate(rule = "self.all(zone, zone.startsWith('eu-'))")]
+    zones: Vec<String>,
+}
+
+#[test]
+fn satisfied_rules_pass() {
+    let scaling = Scaling {
+        min_replicas: 1,
+        max_replicas: 3,
+        zones: vec!["eu-west-1".to_string()],
+    };
+    assert!(scaling.validate().is_ok());
+}
+
+#[test]
+fn violated_rules_are_reported() {
+    let scaling = Scaling {
+        min_replicas: 4,
+        max_replicas: 3,
+        zones: vec!["us-east-1".to_string()],
+    };
+    let errors = scaling.validate().unwrap_err();
+    assert_eq!(paths(&errors), ["zones", ""]);
+    assert!(errors
+        .iter()
+        .all(|error| error.kind == ValidationErrorKind::Rule));
+    assert_eq!(
+        errors.to_string(),
+        "Field 'zones' failed rule 'self.all(zone, zone.startsWith('eu-'))'.\n\
+         minReplicas must not exceed maxReplicas"
+    );
+}
+
+#[derive(ChoreoValidate, Serialize)]
+struct Hosts {
+    #[cvalidate(rule = "self.size() <= 2", message = "{path} allows at most two hosts")]
+    #[cvalidate(
+        rule = "self.all(host, host.endsWith('.com'))",
+        message = "{path} must be .com"
+    )]
+    hosts: Vec<String>,
+}
+
+#[test]
+fn several_rules_on_one_field() {
+    let hosts = Hosts {
+        hosts: vec![
+            "a.com".to_string(),
+            "b.org".to_string(),
+            "c.com".to_string(),
+        ],
+    };
+    let errors = hosts.validate().unwrap_err();
+    assert_eq!(paths(&errors), ["hosts", "hosts"]);
+    assert_eq!(
+        errors.to_string(),
+        "hosts allows at most two hosts\nhosts must be .com"
+    );
+
+    let hosts = Hosts {
+        hosts: vec!["a.com".to_string()],
+    };
+    assert!(hosts.validate().is_ok());
+}
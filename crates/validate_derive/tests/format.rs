@@ -0,0 +1,57 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate)]
+struct Endpoint {
+    #[cvalidate(format = "dns1123_label")]
+    name: String,
+    #[cvalidate(format = "ipv4")]
+    address: Option<String>,
+    #[cvalidate(items(format = "cidr"))]
+    allowed: Vec<String>,
+    #[cvalidate(format = "date_time")]
+    created: String,
+}
+
+#[test]
+fn valid_formats_pass() {
+    let endpoint = Endpoint {
+        name: "api".to_string(),
+        address: Some("10.0.0.1".to_string()),
+        allowed: vec!["10.0.0.0/8".to_string()],
+        created: "2024-01-02T15:04:05Z".to_string(),
+    };
+    assert!(endpoint.validate().is_ok());
+    let endpoint = Endpoint {
+        address: None,
+        allowed: Vec::new(),
+        ..endpoint
+    };
+    assert!(endpoint.validate().is_ok());
+}
+
+#[test]
+fn invalid_formats_are_reported() {
+    let endpoint = Endpoint {
+        name: "API".to_string(),
+        address: Some("10.0.0".to_string()),
+        allowed: vec!["10.0.0.0/8".to_string(), "10.0.0.0/40".to_string()],
+        created: "yesterday".to_string(),
+    };
+    let errors = endpoint.validate().unwrap_err();
+    assert_eq!(paths(&errors), ["name", "address", "allowed[1]", "created"]);
+    assert!(errors
+        .iter()
+        .all(|error| error.kind == ValidationErrorKind::InvalidFormat));
+    assert_eq!(
+        errors.to_string().lines().next(),
+        Some("Field 'name' must be a valid dns1123_label.")
+    );
+    assert_eq!(
+        errors.iter().nth(2).unwrap().value.as_deref(),
+        Some("10.0.0.0/40")
+    );
+}
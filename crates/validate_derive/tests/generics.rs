@@ -0,0 +1,59 @@
+use choreo_api::Validate;
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate)]
+struct Port {
+    #[cvalidate(ge = 1)]
+    number: u32,
+}
+
+/// A newtype is located at the path of its field
+#[derive(ChoreoValidate)]
+struct Name(#[cvalidate(min_length = 1)] String);
+
+#[derive(ChoreoValidate)]
+struct Range(#[cvalidate(ge = 0)] i32, #[cvalidate(le = 10)] i32);
+
+#[derive(ChoreoValidate)]
+struct Spec<P> {
+    name: Name,
+    range: Range,
+    primary: P,
+    others: Vec<P>,
+}
+
+#[derive(ChoreoValidate)]
+struct Wrapper<T>(T)
+where
+    T: Validate;
+
+#[test]
+fn generic_fields_are_validated() {
+    let spec = Spec {
+        name: Name(String::new()),
+        range: Range(-1, 11),
+        primary: Port { number: 0 },
+        others: vec![Port { number: 1 }, Port { number: 0 }],
+    };
+    let errors = spec.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        [
+            "name",
+            "range[0]",
+            "range[1]",
+            "primary.number",
+            "others[1].number"
+        ]
+    );
+}
+
+#[test]
+fn wrappers_are_transparent() {
+    let errors = Wrapper(Port { number: 0 }).validate().unwrap_err();
+    assert_eq!(paths(&errors), ["number"]);
+    assert!(Wrapper(Name("web".to_string())).validate().is_ok());
+}
@@ -0,0 +1,78 @@
+use choreo_api::Validate;
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate)]
+struct Account {
+    #[cvalidate(min_length = 3, pattern = r"^[a-z]{1,3}$", fn = check_name)]
+    name: String,
+    #[cvalidate(pattern = "^key=[a-z]+$")]
+    selector: String,
+    // empty entries, e.g. after a trailing `,`, are ignored
+    #[cvalidate("minLength=2, pattern=^[a-z]+$,")]
+    legacy: String,
+    #[cvalidate(min_length = 1)]
+    #[cvalidate(max_length = 4)]
+    stacked: String,
+}
+
+impl Account {
+    fn check_name(&self) -> Result<(), String> {
+        if self.name == "bad" {
+            return Err("name is reserved".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn valid() -> Account {
+    Account {
+        name: "abc".to_string(),
+        selector: "key=value".to_string(),
+        legacy: "ab".to_string(),
+        stacked: "abcd".to_string(),
+    }
+}
+
+#[test]
+fn commas_and_equals_in_values_are_kept() {
+    assert!(valid().validate().is_ok());
+    let account = Account {
+        name: "ab".to_string(),
+        selector: "key=Value".to_string(),
+        ..valid()
+    };
+    let errors = account.validate().unwrap_err();
+    assert_eq!(paths(&errors), ["name", "selector"]);
+    assert_eq!(
+        errors.iter().nth(1).unwrap().constraint.as_deref(),
+        Some("^key=[a-z]+$")
+    );
+}
+
+#[test]
+fn function_rules_are_called() {
+    let account = Account {
+        name: "bad".to_string(),
+        ..valid()
+    };
+    let errors = account.validate().unwrap_err();
+    let error = errors.iter().next().unwrap();
+    assert_eq!(error.message.as_deref(), Some("name is reserved"));
+    assert_eq!(error.constraint.as_deref(), Some("check_name"));
+}
+
+#[test]
+fn legacy_string_form_and_stacked_attributes() {
+    let account = Account {
+        legacy: "A".to_string(),
+        stacked: "abcde".to_string(),
+        ..valid()
+    };
+    assert_eq!(
+        paths(&account.validate().unwrap_err()),
+        ["legacy", "legacy", "stacked"]
+    );
+}
@@ -0,0 +1,67 @@
+use choreo_api::validation_error::render_template;
+use choreo_api::Validate;
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct User {
+    #[cvalidate(
+        min_length = 3,
+        message = "{path} needs at least {limit} characters, got {value}",
+        code = "user.name.too_short"
+    )]
+    name: String,
+    #[cvalidate(format = "email", message = "'{value}' is not an email address")]
+    email: String,
+    #[cvalidate(le = 150, code = "user.age")]
+    age: u32,
+    #[cvalidate(items(max_length = 3, message = "tag {path} is longer than {limit}"))]
+    tags: Vec<String>,
+}
+
+#[test]
+fn messages_and_codes_replace_the_defaults() {
+    let user = User {
+        name: "al".to_string(),
+        email: "al".to_string(),
+        age: 200,
+        tags: vec!["ok".to_string(), "long".to_string()],
+    };
+    let errors = user.validate().unwrap_err();
+    let messages = errors.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "name needs at least 3 characters, got 2",
+            "'al' is not an email address",
+            "Field 'age' must be <= 150.",
+            "tag tags[1] is longer than 3",
+        ]
+    );
+    let codes = errors.iter().map(|error| error.code()).collect::<Vec<_>>();
+    assert_eq!(
+        codes,
+        [
+            "user.name.too_short",
+            "invalid_format",
+            "user.age",
+            "too_long"
+        ]
+    );
+}
+
+#[test]
+fn templates_are_rendered() {
+    let resolve = |name: &str| (name == "path").then(|| "spec.name".to_string());
+    assert_eq!(
+        render_template("{path} is {{literal}}", resolve),
+        Ok("spec.name is {literal}".to_string())
+    );
+    assert_eq!(
+        render_template("{other}", resolve),
+        Err("unknown placeholder `{other}` in message template".to_string())
+    );
+    assert_eq!(
+        render_template("{path", resolve),
+        Err("unmatched `{` in message template".to_string())
+    );
+}
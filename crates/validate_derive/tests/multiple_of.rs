@@ -0,0 +1,46 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Sizes {
+    #[cvalidate(mo = 0.1)]
+    step: f64,
+    #[cvalidate(mo = 0.1)]
+    small_step: f32,
+    #[cvalidate(mo = 1e-12)]
+    precision: f64,
+    #[cvalidate(mo(0.5, 1e-3))]
+    coarse: Option<f64>,
+    #[cvalidate(mo = 5)]
+    count: u32,
+}
+
+#[test]
+fn multiples_pass() {
+    let sizes = Sizes {
+        step: 0.3,
+        small_step: 0.3,
+        precision: 3e-12,
+        coarse: Some(2.5004),
+        count: 15,
+    };
+    assert!(sizes.validate().is_ok());
+    // large multiples accumulate a rounding error far above an absolute tolerance
+    let sizes = Sizes { step: 1000000000.3, small_step: 12345.6, ..sizes };
+    assert!(sizes.validate().is_ok());
+}
+
+#[test]
+fn other_values_are_reported() {
+    let sizes = Sizes {
+        step: 0.35,
+        small_step: 0.25,
+        precision: 3.5e-12,
+        coarse: Some(2.51),
+        count: 16,
+    };
+    let errors = sizes.validate().unwrap_err();
+    let paths = errors.iter().map(|error| error.path.to_string()).collect::<Vec<_>>();
+    assert_eq!(paths, ["step", "small_step", "precision", "coarse", "count"]);
+    assert!(errors.iter().all(|error| error.kind == ValidationErrorKind::NotMultipleOf));
+}
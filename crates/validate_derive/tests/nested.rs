@@ -0,0 +1,90 @@
+use choreo_api::Validate;
+use std::collections::{BTreeMap, HashMap};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate, Default)]
+struct Port {
+    #[cvalidate(ge = 1, le = 65535)]
+    number: u32,
+}
+
+#[allow(dead_code)]
+#[derive(ChoreoValidate, Default)]
+struct Service {
+    port: Port,
+    backup: Option<Port>,
+    ports: Vec<Port>,
+    by_name: HashMap<String, Port>,
+    sorted: BTreeMap<String, Port>,
+    boxed: Box<Port>,
+    #[cvalidate(dive)]
+    explicit: Port,
+    #[cvalidate(skip)]
+    skipped: Port,
+}
+
+#[test]
+fn nested_values_are_validated_without_attribute() {
+    let service = Service {
+        backup: Some(Port::default()),
+        ports: vec![Port { number: 80 }, Port::default()],
+        by_name: HashMap::from([("http".to_string(), Port::default())]),
+        sorted: BTreeMap::from([("grpc".to_string(), Port::default())]),
+        ..Default::default()
+    };
+    let errors = service.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        [
+            "port.number",
+            "backup.number",
+            "ports[1].number",
+            r#"by_name["http"].number"#,
+            r#"sorted["grpc"].number"#,
+            "boxed.number",
+            "explicit.number",
+        ]
+    );
+}
+
+#[test]
+fn valid_nested_values_pass() {
+    let port = || Port { number: 443 };
+    let service = Service {
+        port: port(),
+        backup: None,
+        ports: vec![port()],
+        by_name: HashMap::new(),
+        sorted: BTreeMap::from([("grpc".to_string(), port())]),
+        boxed: Box::new(port()),
+        explicit: port(),
+        skipped: Port::default(),
+    };
+    assert!(service.validate().is_ok());
+}
+
+#[derive(ChoreoValidate)]
+struct Spec {
+    #[cvalidate(immutable)]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct Resource {
+    spec: Spec,
+    previous: Option<Spec>,
+}
+
+#[test]
+fn nested_transitions_are_checked_without_attribute() {
+    let resource = |name: &str| Resource {
+        spec: Spec { name: name.to_string() },
+        previous: Some(Spec { name: name.to_string() }),
+    };
+    let errors = resource("b").validate_update(&resource("a")).unwrap_err();
+    assert_eq!(paths(&errors), ["spec.name", "previous.name"]);
+    assert!(resource("a").validate_update(&resource("a")).is_ok());
+}
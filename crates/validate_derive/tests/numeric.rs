@@ -0,0 +1,102 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use std::num::{NonZeroI64, NonZeroU32};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate)]
+struct Limits {
+    #[cvalidate(ge = 1, le = 100)]
+    percent: u8,
+    #[cvalidate(gt = -10, lt = 10)]
+    offset: i8,
+    #[cvalidate(le = 1000)]
+    weight: i16,
+    #[cvalidate(ge = 1024)]
+    port: u16,
+    #[cvalidate(le = 16)]
+    workers: usize,
+    #[cvalidate(ge = -1)]
+    timeout: isize,
+    #[cvalidate(le = 170141183460469231731687303715884105727)]
+    big: i128,
+    #[cvalidate(gt = 0)]
+    huge: u128,
+    #[cvalidate(ge = 0.5, le = 1.5)]
+    ratio: f32,
+    #[cvalidate(le = 64)]
+    replicas: NonZeroU32,
+    #[cvalidate(ge = -5)]
+    delta: Option<NonZeroI64>,
+    #[cvalidate(finite)]
+    score: f64,
+    #[cvalidate(finite)]
+    scale: Option<f32>,
+}
+
+fn valid() -> Limits {
+    Limits {
+        percent: 50,
+        offset: -9,
+        weight: 1000,
+        port: 8080,
+        workers: 16,
+        timeout: -1,
+        big: i128::MAX,
+        huge: 1,
+        ratio: 1.5,
+        replicas: NonZeroU32::new(3).unwrap(),
+        delta: NonZeroI64::new(-5),
+        score: 0.25,
+        scale: Some(2.0),
+    }
+}
+
+#[test]
+fn values_in_range_pass() {
+    assert!(valid().validate().is_ok());
+}
+
+#[test]
+fn values_out_of_range_are_reported() {
+    let limits = Limits {
+        percent: 0,
+        offset: 10,
+        weight: 1001,
+        port: 80,
+        workers: 17,
+        timeout: -2,
+        big: i128::MAX,
+        huge: 0,
+        ratio: 1.6,
+        replicas: NonZeroU32::new(65).unwrap(),
+        delta: NonZeroI64::new(-6),
+        score: 0.25,
+        scale: Some(2.0),
+    };
+    let errors = limits.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        [
+            "percent", "offset", "weight", "port", "workers", "timeout", "huge", "ratio",
+            "replicas", "delta",
+        ]
+    );
+    assert!(errors
+        .iter()
+        .all(|error| error.kind == ValidationErrorKind::OutOfRange));
+}
+
+#[test]
+fn non_finite_floats_are_reported() {
+    for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+        let limits = Limits {
+            score: value,
+            scale: Some(value as f32),
+            ..valid()
+        };
+        let errors = limits.validate().unwrap_err();
+        assert_eq!(paths(&errors), ["score", "scale"], "{value}");
+    }
+}
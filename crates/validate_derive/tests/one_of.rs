@@ -0,0 +1,57 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate)]
+struct Port {
+    #[cvalidate(one_of = ["TCP", "UDP", "SCTP"])]
+    protocol: String,
+    #[cvalidate(enum("http", "grpc"))]
+    app_protocol: Option<String>,
+    #[cvalidate(one_of = [80, 443])]
+    number: u16,
+    #[cvalidate("one_of=IPv4|IPv6")]
+    family: String,
+    #[cvalidate(one_of = ["a", "b"])]
+    zones: Vec<String>,
+    #[cvalidate(one_of = [1, 2])]
+    weights: Option<Vec<u32>>,
+}
+
+#[test]
+fn allowed_values_pass() {
+    let port = Port {
+        protocol: "TCP".to_string(),
+        app_protocol: Some("grpc".to_string()),
+        number: 443,
+        family: "IPv6".to_string(),
+        zones: vec!["a".to_string(), "b".to_string()],
+        weights: Some(vec![1, 2, 1]),
+    };
+    assert!(port.validate().is_ok());
+    let port = Port { app_protocol: None, weights: None, ..port };
+    assert!(port.validate().is_ok());
+}
+
+#[test]
+fn other_values_are_reported() {
+    let port = Port {
+        protocol: "ICMP".to_string(),
+        app_protocol: Some("ftp".to_string()),
+        number: 8080,
+        family: "IPv5".to_string(),
+        zones: vec!["a".to_string(), "c".to_string()],
+        weights: Some(vec![3, 1]),
+    };
+    let errors = port.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        ["protocol", "app_protocol", "number", "family", "zones[1]", "weights[0]"]
+    );
+    assert!(errors.iter().all(|error| error.kind == ValidationErrorKind::NotOneOf));
+    let protocol = errors.iter().next().unwrap();
+    assert_eq!(protocol.constraint.as_deref(), Some("[TCP, UDP, SCTP]"));
+    assert_eq!(protocol.value.as_deref(), Some("ICMP"));
+}
@@ -0,0 +1,44 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Label {
+    #[cvalidate(pattern = r"^[a-z]{1,3}(-[a-z]{1,3})*$")]
+    name: String,
+    #[cvalidate(pattern = "^v[0-9]+$")]
+    version: Option<String>,
+}
+
+#[test]
+fn matching_values_pass() {
+    for name in ["a", "abc", "ab-cde"] {
+        let label = Label {
+            name: name.to_string(),
+            version: Some("v1".to_string()),
+        };
+        assert!(label.validate().is_ok(), "{name}");
+    }
+}
+
+#[test]
+fn other_values_are_reported_on_every_call() {
+    let label = Label {
+        name: "abcd".to_string(),
+        version: Some("1".to_string()),
+    };
+    for _ in 0..3 {
+        let errors = label.validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+        let name = errors.iter().next().unwrap();
+        assert_eq!(name.kind, ValidationErrorKind::PatternMismatch);
+        assert_eq!(
+            name.constraint.as_deref(),
+            Some(r"^[a-z]{1,3}(-[a-z]{1,3})*$")
+        );
+    }
+    let label = Label {
+        name: "abc".to_string(),
+        version: None,
+    };
+    assert!(label.validate().is_ok());
+}
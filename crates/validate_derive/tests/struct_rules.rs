@@ -0,0 +1,79 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate, Default)]
+#[cvalidate(compare(min_replicas <= max_replicas))]
+#[cvalidate(mutually_exclusive(image, source))]
+#[cvalidate(at_least_one_of(image, source, command))]
+#[cvalidate(required_if(tls_enabled, cert_ref))]
+struct Deployment {
+    min_replicas: u32,
+    max_replicas: Option<u32>,
+    image: Option<String>,
+    source: Option<String>,
+    command: Vec<String>,
+    tls_enabled: bool,
+    cert_ref: Option<String>,
+}
+
+fn valid() -> Deployment {
+    Deployment {
+        min_replicas: 1,
+        max_replicas: Some(3),
+        image: Some("nginx".to_string()),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn satisfied_rules_pass() {
+    assert!(valid().validate().is_ok());
+    let deployment = Deployment {
+        max_replicas: None,
+        tls_enabled: true,
+        cert_ref: Some("cert".to_string()),
+        ..valid()
+    };
+    assert!(deployment.validate().is_ok());
+}
+
+#[test]
+fn violations_point_to_the_fields() {
+    let deployment = Deployment {
+        min_replicas: 5,
+        source: Some("git".to_string()),
+        tls_enabled: true,
+        ..valid()
+    };
+    let errors = deployment.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        ["min_replicas", "image", "source", "cert_ref"]
+    );
+    assert_eq!(
+        errors.to_string(),
+        "Field 'min_replicas' must be <= max_replicas.\n\
+         Field 'image' must not be set together with 'source'.\n\
+         Field 'source' must not be set together with 'image'.\n\
+         Field 'cert_ref' is required when 'tls_enabled' is set"
+    );
+}
+
+#[test]
+fn at_least_one_field_is_required() {
+    let deployment = Deployment {
+        image: None,
+        ..valid()
+    };
+    let errors = deployment.validate().unwrap_err();
+    assert_eq!(paths(&errors), ["image", "source", "command"]);
+    let error = errors.iter().next().unwrap();
+    assert_eq!(error.kind, ValidationErrorKind::Required);
+    assert_eq!(
+        error.constraint.as_deref(),
+        Some("none of 'source', 'command' is set")
+    );
+}
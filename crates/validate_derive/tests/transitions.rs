@@ -0,0 +1,97 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use serde::Serialize;
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate, Serialize, Clone)]
+#[cvalidate(
+    rule = "self.generation >= oldSelf.generation",
+    message = "generation went back"
+)]
+struct Volume {
+    #[cvalidate(immutable)]
+    storage_class: String,
+    #[cvalidate(immutable_once_set)]
+    volume_name: Option<String>,
+    #[cvalidate(increase_only)]
+    size: u64,
+    #[cvalidate(rule = "self == oldSelf || oldSelf == 'Pending'")]
+    phase: String,
+    generation: u64,
+}
+
+fn volume() -> Volume {
+    Volume {
+        storage_class: "fast".to_string(),
+        volume_name: None,
+        size: 10,
+        phase: "Pending".to_string(),
+        generation: 1,
+    }
+}
+
+#[test]
+fn allowed_updates_pass() {
+    let old = volume();
+    let new = Volume {
+        volume_name: Some("pv-1".to_string()),
+        size: 20,
+        phase: "Bound".to_string(),
+        generation: 2,
+        ..old.clone()
+    };
+    assert!(new.validate_update(&old).is_ok());
+    assert!(new.validate_update(&new).is_ok());
+}
+
+#[test]
+fn forbidden_updates_are_reported() {
+    let old = Volume {
+        volume_name: Some("pv-1".to_string()),
+        phase: "Bound".to_string(),
+        generation: 2,
+        ..volume()
+    };
+    let new = Volume {
+        storage_class: "slow".to_string(),
+        volume_name: Some("pv-2".to_string()),
+        size: 5,
+        phase: "Released".to_string(),
+        generation: 1,
+    };
+    let errors = new.validate_update(&old).unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        ["storage_class", "volume_name", "size", "phase", ""]
+    );
+    let kinds = errors.iter().map(|error| error.kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ValidationErrorKind::Immutable,
+            ValidationErrorKind::Immutable,
+            ValidationErrorKind::InvalidTransition,
+            ValidationErrorKind::Rule,
+            ValidationErrorKind::Rule,
+        ]
+    );
+    assert_eq!(
+        errors.to_string(),
+        "Field 'storage_class' is immutable.\n\
+         Field 'volume_name' is immutable once set.\n\
+         Field 'size' may only increase.\n\
+         Field 'phase' failed rule 'self == oldSelf || oldSelf == 'Pending''.\n\
+         generation went back"
+    );
+}
+
+#[test]
+fn transitions_are_not_checked_on_create() {
+    let volume = Volume {
+        generation: 0,
+        ..volume()
+    };
+    assert!(volume.validate().is_ok());
+}
@@ -0,0 +1,15 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct KeysOfList {
+    #[cvalidate(keys(min_length = 1))]
+    names: Vec<String>,
+}
+
+#[derive(ChoreoValidate)]
+struct ItemsOfString {
+    #[cvalidate(items(min_length = 1))]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate, serde::Serialize)]
+#[cvalidate(rule = "self.min <= ")]
+struct Incomplete {
+    min: u32,
+}
+
+#[derive(ChoreoValidate, serde::Serialize)]
+struct UnknownVariable {
+    #[cvalidate(rule = "value > 0")]
+    min: u32,
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct UnknownPlaceholder {
+    #[cvalidate(min_length = 3, message = "{field} is too short")]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct CodeWithoutValue {
+    #[cvalidate(min_length = 3, code)]
+    name: String,
+}
+
+#[derive(ChoreoValidate, serde::Serialize)]
+struct RuleWithLimit {
+    #[cvalidate(rule = "self.size() <= 3", message = "at most {limit} hosts")]
+    hosts: Vec<String>,
+}
+
+#[derive(ChoreoValidate, serde::Serialize)]
+#[cvalidate(rule = "self.min <= self.max", message = "{min} exceeds {max}")]
+struct StructRuleWithPlaceholder {
+    min: u32,
+    max: u32,
+}
+
+fn main() {}
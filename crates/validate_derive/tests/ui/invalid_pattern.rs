@@ -0,0 +1,9 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Label {
+    #[cvalidate(pattern = "^[a-z+$")]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct LegacyMessage {
+    #[cvalidate("minLength=3, message=too short")]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct LegacyWarn {
+    #[cvalidate("maxLength=63, warn")]
+    name: String,
+}
+
+fn main() {}
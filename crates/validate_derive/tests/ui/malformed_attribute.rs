@@ -0,0 +1,34 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct MissingValue {
+    #[cvalidate(min_length = )]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct MissingComma {
+    #[cvalidate(min_length = 1 max_length = 3)]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct WrongValue {
+    #[cvalidate(min_length = "three")]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct MessageWithoutRule {
+    #[cvalidate(message = "too short")]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct DuplicateRule {
+    #[cvalidate(min_length = 1)]
+    #[cvalidate(min_length = 2)]
+    name: String,
+}
+
+fn main() {}
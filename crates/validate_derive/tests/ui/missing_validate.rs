@@ -0,0 +1,14 @@
+use validate_derive::Validate as ChoreoValidate;
+
+struct Quantity(String);
+
+#[derive(ChoreoValidate)]
+struct ResourceSpec {
+    #[cvalidate(min_length = 1)]
+    name: String,
+    limit: Quantity,
+    #[cvalidate(skip)]
+    request: Quantity,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Weights {
+    #[cvalidate(one_of = [1, 2])]
+    weights: Vec<f64>,
+    #[cvalidate(one_of = ["a", 2])]
+    ids: Vec<u32>,
+}
+
+fn main() {}
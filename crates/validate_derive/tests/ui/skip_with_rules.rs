@@ -0,0 +1,17 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Port {
+    #[cvalidate(ge = 1)]
+    number: u32,
+}
+
+#[derive(ChoreoValidate)]
+struct Service {
+    #[cvalidate(skip, min_length = 1)]
+    name: String,
+    #[cvalidate(skip, dive)]
+    port: Port,
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+#[cvalidate(mutually_exclusive(image, sources))]
+struct UnknownField {
+    image: Option<String>,
+    source: Option<String>,
+}
+
+#[derive(ChoreoValidate)]
+#[cvalidate(mutualy_exclusive(image, source))]
+struct Misspelled {
+    image: Option<String>,
+    source: Option<String>,
+}
+
+#[derive(ChoreoValidate)]
+#[cvalidate(at_least_one_of(image))]
+struct SingleField {
+    image: Option<String>,
+}
+
+fn main() {}
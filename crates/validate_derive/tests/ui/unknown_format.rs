@@ -0,0 +1,9 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Endpoint {
+    #[cvalidate(format = "hostname")]
+    host: String,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Abbreviated {
+    #[cvalidate(minLen = 3)]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct Misspelled {
+    #[cvalidate(min_lenght = 3)]
+    name: String,
+}
+
+#[derive(ChoreoValidate)]
+struct Unknown {
+    #[cvalidate(colour = "red")]
+    name: String,
+}
+
+fn main() {}
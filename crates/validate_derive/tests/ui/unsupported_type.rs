@@ -0,0 +1,15 @@
+use validate_derive::Validate as ChoreoValidate;
+
+#[derive(ChoreoValidate)]
+struct Length {
+    #[cvalidate(min_length = 3)]
+    replicas: u32,
+}
+
+#[derive(ChoreoValidate)]
+struct Required {
+    #[cvalidate(required)]
+    name: String,
+}
+
+fn main() {}
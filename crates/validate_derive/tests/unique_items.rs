@@ -0,0 +1,105 @@
+use choreo_api::{Validate, ValidationErrorKind};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate)]
+struct PortSpec {
+    name: String,
+    protocol: String,
+    #[cvalidate(immutable)]
+    port: u16,
+}
+
+fn port(name: &str, protocol: &str, port: u16) -> PortSpec {
+    PortSpec {
+        name: name.to_string(),
+        protocol: protocol.to_string(),
+        port,
+    }
+}
+
+#[derive(ChoreoValidate)]
+struct Service {
+    #[cvalidate(unique_items)]
+    hosts: Vec<String>,
+    #[cvalidate(unique_items)]
+    zones: Option<Vec<String>>,
+    #[cvalidate(list_map_keys = "name,protocol")]
+    ports: Vec<PortSpec>,
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn unique_items_pass() {
+    let service = Service {
+        hosts: strings(&["a", "b"]),
+        zones: None,
+        ports: vec![port("http", "TCP", 80), port("http", "UDP", 80)],
+    };
+    assert!(service.validate().is_ok());
+}
+
+#[test]
+fn every_duplicate_is_reported_at_its_index() {
+    let service = Service {
+        hosts: strings(&["a", "b", "a", "a"]),
+        zones: Some(strings(&["eu", "eu"])),
+        ports: vec![port("http", "TCP", 80), port("http", "TCP", 8080)],
+    };
+    let errors = service.validate().unwrap_err();
+    assert_eq!(
+        paths(&errors),
+        ["hosts[2]", "hosts[3]", "zones[1]", "ports[1]"]
+    );
+    assert!(errors
+        .iter()
+        .all(|error| error.kind == ValidationErrorKind::Duplicate));
+    assert_eq!(
+        errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+        [
+            "Field 'hosts[2]' is a duplicate of 'hosts[0]'.",
+            "Field 'hosts[3]' is a duplicate of 'hosts[0]'.",
+            "Field 'zones[1]' is a duplicate of 'zones[0]'.",
+            "Field 'ports[1]' is a duplicate of 'ports[0]': same name, protocol.",
+        ]
+    );
+}
+
+fn service(ports: Vec<PortSpec>) -> Service {
+    Service {
+        hosts: Vec::new(),
+        zones: None,
+        ports,
+    }
+}
+
+#[test]
+fn list_map_items_are_matched_by_key_on_update() {
+    let old = service(vec![port("http", "TCP", 80), port("grpc", "TCP", 9090)]);
+    let reordered = service(vec![port("grpc", "TCP", 9090), port("http", "TCP", 80)]);
+    assert!(reordered.validate_update(&old).is_ok());
+
+    let changed = service(vec![port("dns", "UDP", 53), port("http", "TCP", 8080)]);
+    let errors = changed.validate_update(&old).unwrap_err();
+    assert_eq!(paths(&errors), ["ports[1].port"]);
+}
+
+#[derive(ChoreoValidate)]
+struct Weights {
+    // floats only implement `PartialEq`, they are compared pairwise
+    #[cvalidate(unique_items)]
+    weights: Vec<f64>,
+}
+
+#[test]
+fn items_without_hash_are_compared() {
+    let weights = Weights {
+        weights: vec![0.5, f64::NAN, 0.5, 1.0],
+    };
+    assert_eq!(paths(&weights.validate().unwrap_err()), ["weights[2]"]);
+}
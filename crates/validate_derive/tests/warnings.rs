@@ -0,0 +1,55 @@
+use choreo_api::{Severity, Validate, ValidationErrorKind};
+use validate_derive::Validate as ChoreoValidate;
+
+mod common;
+use common::paths;
+
+#[derive(ChoreoValidate, Default)]
+struct Service {
+    #[cvalidate(warn, max_length = 10)]
+    #[cvalidate(min_length = 1)]
+    name: String,
+    #[cvalidate(deprecated = "use ports instead")]
+    port: Option<u16>,
+    #[cvalidate(warn, items(le = 1024))]
+    ports: Vec<u16>,
+}
+
+#[test]
+fn warnings_do_not_reject_the_object() {
+    let service = Service {
+        name: "a-very-long-name".to_string(),
+        port: Some(80),
+        ports: vec![80, 8080],
+    };
+    assert!(service.validate().is_ok());
+
+    let report = service.report();
+    assert!(report.is_valid());
+    assert!(report.errors.is_empty());
+    assert_eq!(paths(&report.warnings), ["name", "port", "ports[1]"]);
+    assert!(report
+        .warnings
+        .iter()
+        .all(|warning| warning.severity == Severity::Warning));
+    let deprecated = report.warnings.iter().nth(1).unwrap();
+    assert_eq!(deprecated.kind, ValidationErrorKind::Deprecated);
+    assert_eq!(
+        deprecated.to_string(),
+        "Field 'port' is deprecated: use ports instead."
+    );
+    assert_eq!(report.into_result().map(|warnings| warnings.len()), Ok(3));
+}
+
+#[test]
+fn errors_are_separated_from_warnings() {
+    let service = Service {
+        port: Some(80),
+        ..Default::default()
+    };
+    let report = service.report();
+    assert!(!report.is_valid());
+    assert_eq!(paths(&report.errors), ["name"]);
+    assert_eq!(paths(&report.warnings), ["port"]);
+    assert_eq!(paths(&service.validate().unwrap_err()), ["name"]);
+}
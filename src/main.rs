@@ -1,7 +1,6 @@
 //use std::default;
 //use std::collections::{BTreeMap, HashMap};
 use serde::{Deserialize, Serialize};
-use serde_json;
 use default_derive::Default as ChoreoDefault;
 use validate_derive::Validate as ChoreoValidate;
 use choreo_derive::ChoreoResource;